@@ -1,10 +1,13 @@
 //! Authenticate request
 
-use reqwest::{IntoUrl, StatusCode, Url};
+use reqwest::{IntoUrl, Method, StatusCode, Url};
 use serde_derive::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::client::{Client, DEFAULT_CLIENT};
 use crate::consts::DEFAULT_SERVER;
+use crate::http::{HttpRequest, HttpResponse};
+use crate::pool::ServerPool;
 use crate::types::{Profile, User};
 use crate::{Error, Result};
 
@@ -42,8 +45,10 @@ struct AuthenticateParams<'a> {
 /// ```
 pub struct AuthenticateBuilder<'a> {
     params: AuthenticateParams<'a>,
-    server: Url,
+    servers: ServerPool,
     endpoint: &'a str,
+    client: Client,
+    answered: Option<Url>,
 }
 
 /// Response body from Mojang server
@@ -76,6 +81,7 @@ impl Default for AgentInfo<'_> {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for AuthenticateParams<'_> {
     fn default() -> AuthenticateParams<'static> {
         AuthenticateParams {
@@ -92,8 +98,10 @@ impl Default for AuthenticateBuilder<'_> {
     fn default() -> AuthenticateBuilder<'static> {
         AuthenticateBuilder {
             params: AuthenticateParams::default(),
-            server: (*DEFAULT_SERVER).clone(),
+            servers: ServerPool::single((*DEFAULT_SERVER).clone()),
             endpoint: "/authenticate",
+            client: DEFAULT_CLIENT.clone(),
+            answered: None,
         }
     }
 }
@@ -143,18 +151,56 @@ impl<'a> AuthenticateBuilder<'a> {
 
     /// Set base url, default is `https://authserver.mojang.com`.
     pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut AuthenticateBuilder<'a>> {
-        self.server = server.into_url()?;
+        self.servers = ServerPool::single(server.into_url()?);
         Ok(self)
     }
 
+    /// Set mirrored servers to fail over between, instead of a single
+    /// [server](Self::server).
+    pub fn servers(&mut self, servers: &ServerPool) -> &mut AuthenticateBuilder<'a> {
+        self.servers = servers.clone();
+        self
+    }
+
     /// set endpoint, default is `/authenticate`.
     pub fn endpoint(&mut self, endpoint: &'a str) -> &mut AuthenticateBuilder<'a> {
         self.endpoint = endpoint;
         self
     }
 
+    /// Set client which sends the request, default is [`Client::default`].
+    pub fn client(&mut self, client: &Client) -> &mut AuthenticateBuilder<'a> {
+        self.client = client.clone();
+        self
+    }
+
+    /// Get the server which answered the last request, see
+    /// [servers](Self::servers).
+    pub fn answered_server(&self) -> Option<&Url> {
+        self.answered.as_ref()
+    }
+
+    /// Build the request without sending it, for the first server to try.
+    /// See [http](crate::http).
+    pub fn http_request(&mut self) -> Result<HttpRequest> {
+        self.check()?;
+        self.http_request_to(&self.servers.candidates()[0])
+    }
+
     /// Make a request with the given parameters.
     pub async fn request(&mut self) -> Result<AuthenticateResponse> {
+        self.check()?;
+
+        let (server, response) = self
+            .servers
+            .send(&self.client, false, |server| self.http_request_to(server))
+            .await?;
+        self.answered = Some(server);
+
+        parse_response(&response)
+    }
+
+    fn check(&mut self) -> Result<()> {
         if self.params.username.is_none() {
             return Err(Error::MissingField("username"));
         }
@@ -165,16 +211,18 @@ impl<'a> AuthenticateBuilder<'a> {
             self.params.client_token = Some(Uuid::new_v4());
         }
 
-        let client = reqwest::Client::new();
-        let response = client
-            .post(self.server.join(self.endpoint)?)
-            .json(&self.params)
-            .send()
-            .await?;
+        Ok(())
+    }
 
-        match response.status() {
-            StatusCode::OK => Ok(response.json().await?),
-            _ => Err(Error::from_response(response).await),
-        }
+    fn http_request_to(&self, server: &Url) -> Result<HttpRequest> {
+        HttpRequest::json(Method::POST, server.join(self.endpoint)?, &self.params)
+    }
+}
+
+/// Read the response of an authenticate request, see [http](crate::http).
+pub fn parse_response(response: &HttpResponse) -> Result<AuthenticateResponse> {
+    match response.status {
+        StatusCode::OK => response.json(),
+        _ => Err(response.error()),
     }
 }
@@ -0,0 +1,192 @@
+//! Blocked servers
+//!
+//! Mojang publishes the servers which the official client refuses to join at
+//! `https://sessionserver.mojang.com/blockedservers`, as SHA-1 hashes of
+//! lowercased hostnames and patterns such as `*.example.com` or `10.0.0.*`.
+
+use reqwest::{IntoUrl, Method, StatusCode, Url};
+use sha1::{Digest, Sha1};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::client::{Client, DEFAULT_CLIENT};
+use crate::consts::DEFAULT_SESSION_SERVER;
+use crate::http::HttpRequest;
+use crate::Result;
+
+/// The list of blocked servers
+#[derive(Clone, Debug, Default)]
+pub struct BlockedServers {
+    hashes: HashSet<String>,
+}
+
+/// `BlockedServersClient` downloads the list of blocked servers and caches it
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::blocked_servers::BlockedServersClient;
+/// # use sage_auth::error::Result;
+/// # async fn anonymous() -> Result<()> {
+/// let client = BlockedServersClient::new();
+/// if client.is_blocked("mc.example.com").await? {
+///     // Refuse to connect
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct BlockedServersClient {
+    server: Url,
+    endpoint: String,
+    cache_ttl: Duration,
+    cache: Mutex<Option<(Instant, Arc<BlockedServers>)>>,
+    client: Client,
+}
+
+impl BlockedServers {
+    /// Create the list from hex encoded SHA-1 hashes.
+    pub fn from_hashes<I, S>(hashes: I) -> BlockedServers
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        BlockedServers {
+            hashes: hashes
+                .into_iter()
+                .map(|hash| hash.as_ref().trim().to_ascii_lowercase())
+                .filter(|hash| !hash.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Parse the list in the format of `/blockedservers`, one hash per line.
+    pub fn parse(body: &str) -> BlockedServers {
+        BlockedServers::from_hashes(body.lines())
+    }
+
+    /// Download the list from `url`, such as
+    /// `https://sessionserver.mojang.com/blockedservers`.
+    pub async fn fetch<T: IntoUrl>(url: T) -> Result<BlockedServers> {
+        BlockedServers::fetch_with_client(&DEFAULT_CLIENT, url).await
+    }
+
+    /// Download the list from `url` with `client`.
+    pub async fn fetch_with_client<T: IntoUrl>(client: &Client, url: T) -> Result<BlockedServers> {
+        let response = client
+            .send(HttpRequest::new(Method::GET, url.into_url()?))
+            .await?;
+
+        match response.status {
+            StatusCode::OK => Ok(BlockedServers::parse(&response.text())),
+            _ => Err(response.error()),
+        }
+    }
+
+    /// Get the number of hashes in the list.
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Whether the server at `host` (a hostname or IPv4 address, without the
+    /// port) is blocked by itself or by any pattern which covers it.
+    pub fn is_blocked(&self, host: &str) -> bool {
+        candidates(host)
+            .iter()
+            .any(|candidate| self.hashes.contains(&hash(candidate)))
+    }
+}
+
+/// Expand `host` into every name which the official client checks: the
+/// host itself, then `a.b.c.*`, `a.b.*`, `a.*` for IPv4 addresses, or
+/// `*.b.c.d`, `*.c.d`, `*.d` for hostnames.
+pub fn candidates(host: &str) -> Vec<String> {
+    let host = host.trim_end_matches('.').to_lowercase();
+    let parts: Vec<_> = host.split('.').collect();
+    let is_ip = parts.len() == 4 && parts.iter().all(|part| part.parse::<u8>().is_ok());
+
+    let mut candidates = vec![host.clone()];
+    if is_ip {
+        for i in (1..parts.len()).rev() {
+            candidates.push(format!("{}.*", parts[..i].join(".")));
+        }
+    } else {
+        for i in 1..parts.len() {
+            candidates.push(format!("*.{}", parts[i..].join(".")));
+        }
+    }
+    candidates
+}
+
+fn hash(candidate: &str) -> String {
+    Sha1::digest(candidate.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+impl Default for BlockedServersClient {
+    fn default() -> BlockedServersClient {
+        BlockedServersClient {
+            server: (*DEFAULT_SESSION_SERVER).clone(),
+            endpoint: "/blockedservers".to_owned(),
+            cache_ttl: Duration::from_secs(60 * 60),
+            cache: Mutex::new(None),
+            client: DEFAULT_CLIENT.clone(),
+        }
+    }
+}
+
+impl BlockedServersClient {
+    pub fn new() -> BlockedServersClient {
+        BlockedServersClient::default()
+    }
+
+    /// Set base url, default is `https://sessionserver.mojang.com`.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut BlockedServersClient> {
+        self.server = server.into_url()?;
+        Ok(self)
+    }
+
+    /// set endpoint, default is `/blockedservers`.
+    pub fn endpoint<S: Into<String>>(&mut self, endpoint: S) -> &mut BlockedServersClient {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Set how long the list is cached, default is one hour.
+    pub fn cache_ttl(&mut self, cache_ttl: Duration) -> &mut BlockedServersClient {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    /// Set client which sends the requests, default is [`Client::default`].
+    pub fn client(&mut self, client: &Client) -> &mut BlockedServersClient {
+        self.client = client.clone();
+        self
+    }
+
+    /// Get the list, downloading it if the cache is empty or outdated.
+    pub async fn blocked_servers(&self) -> Result<Arc<BlockedServers>> {
+        if let Some((fetched_at, list)) = &*self.cache.lock().unwrap() {
+            if fetched_at.elapsed() < self.cache_ttl {
+                return Ok(list.clone());
+            }
+        }
+
+        let list = Arc::new(
+            BlockedServers::fetch_with_client(&self.client, self.server.join(&self.endpoint)?)
+                .await?,
+        );
+        *self.cache.lock().unwrap() = Some((Instant::now(), list.clone()));
+        Ok(list)
+    }
+
+    /// Whether the server at `host` is blocked, see [BlockedServers::is_blocked].
+    pub async fn is_blocked(&self, host: &str) -> Result<bool> {
+        Ok(self.blocked_servers().await?.is_blocked(host))
+    }
+}
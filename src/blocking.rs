@@ -0,0 +1,946 @@
+//! Blocking API
+//!
+//! The builders, clients and [Session] of this module mirror the async
+//! ones, and wait for their requests instead of returning futures. They take
+//! and return the same types and errors. Requests run on a runtime which the
+//! crate starts on first use, so the caller needs no runtime of its own.
+//!
+//! The blocking API must not be used from async code, where it would block
+//! the executor. Other async functions, such as
+//! [BlockedServers::fetch](crate::blocked_servers::BlockedServers::fetch),
+//! can be waited for with [block_on].
+//!
+//! For example:
+//! ```no_run
+//! # use sage_auth::blocking::Session;
+//! # use sage_auth::error::Result;
+//! # fn anonymous() -> Result<()> {
+//! let session = Session::login("USERNAME", "PASSWORD")?;
+//! session.validate()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::Duration;
+use lazy_static::lazy_static;
+use reqwest::{IntoUrl, Url};
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration as StdDuration;
+use tokio::runtime::{Builder, Runtime};
+use uuid::Uuid;
+
+use crate::auth::AuthenticateResponse;
+use crate::blocked_servers::BlockedServers;
+use crate::certificates::PlayerCertificates;
+use crate::client::Client;
+use crate::http::HttpRequest;
+use crate::launch::LaunchArguments;
+use crate::pool::ServerPool;
+use crate::profile_key::{ProfilePublicKey, ServicesKeys};
+use crate::realms::{Compatibility, PendingInvite, RealmsAddress, RealmsWorld};
+use crate::refresh::RefreshResponse;
+use crate::report::AbuseReport;
+use crate::services::{
+    Cape, Entitlements, MinecraftProfile, NameAvailability, NameChangeInfo, PlayerAttributes,
+    SkinVariant,
+};
+use crate::session::AccountState;
+use crate::signing::PublicKey;
+use crate::types::Profile;
+use crate::Result;
+
+lazy_static! {
+    static ref RUNTIME: Runtime = Builder::new()
+        .threaded_scheduler()
+        .core_threads(1)
+        .thread_name("sage-auth-blocking")
+        .enable_all()
+        .build()
+        .expect("failed to start the runtime of the blocking API");
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Run `future` to completion on the runtime of the blocking API, blocking
+/// the current thread.
+///
+/// ```no_run
+/// # use sage_auth::blocked_servers::BlockedServers;
+/// # use sage_auth::blocking;
+/// # use sage_auth::error::Result;
+/// # fn anonymous() -> Result<()> {
+/// let url = "https://sessionserver.mojang.com/blockedservers";
+/// let blocked = blocking::block_on(BlockedServers::fetch(url))?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+
+    RUNTIME.handle().enter(|| loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    })
+}
+
+/// Blocking [AuthenticateBuilder](crate::auth::AuthenticateBuilder)
+#[derive(Default)]
+pub struct AuthenticateBuilder<'a> {
+    inner: crate::auth::AuthenticateBuilder<'a>,
+}
+
+impl<'a> AuthenticateBuilder<'a> {
+    pub fn new() -> AuthenticateBuilder<'a> {
+        AuthenticateBuilder::default()
+    }
+
+    /// Set username, required
+    pub fn username(&mut self, username: &'a str) -> &mut AuthenticateBuilder<'a> {
+        self.inner.username(username);
+        self
+    }
+
+    /// Set password, required
+    pub fn password(&mut self, password: &'a str) -> &mut AuthenticateBuilder<'a> {
+        self.inner.password(password);
+        self
+    }
+
+    /// Set client token, a random one if not set.
+    pub fn client_token(&mut self, client_token: Uuid) -> &mut AuthenticateBuilder<'a> {
+        self.inner.client_token(client_token);
+        self
+    }
+
+    /// Request the user object in the response.
+    pub fn request_user(&mut self) -> &mut AuthenticateBuilder<'a> {
+        self.inner.request_user();
+        self
+    }
+
+    /// Set agent name, default is `Minecraft`.
+    pub fn agent_name(&mut self, agent_name: &'a str) -> &mut AuthenticateBuilder<'a> {
+        self.inner.agent_name(agent_name);
+        self
+    }
+
+    /// Set agent version, default is `1`.
+    pub fn agent_version(&mut self, agent_version: i32) -> &mut AuthenticateBuilder<'a> {
+        self.inner.agent_version(agent_version);
+        self
+    }
+
+    /// Set authentication server.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut AuthenticateBuilder<'a>> {
+        self.inner.server(server)?;
+        Ok(self)
+    }
+
+    /// Set mirrored authentication servers to fail over between.
+    pub fn servers(&mut self, servers: &ServerPool) -> &mut AuthenticateBuilder<'a> {
+        self.inner.servers(servers);
+        self
+    }
+
+    /// Set endpoint, default is `/authenticate`.
+    pub fn endpoint(&mut self, endpoint: &'a str) -> &mut AuthenticateBuilder<'a> {
+        self.inner.endpoint(endpoint);
+        self
+    }
+
+    /// Set client which sends the request.
+    pub fn client(&mut self, client: &Client) -> &mut AuthenticateBuilder<'a> {
+        self.inner.client(client);
+        self
+    }
+
+    /// Get the server which answered the last request.
+    pub fn answered_server(&self) -> Option<&Url> {
+        self.inner.answered_server()
+    }
+
+    /// Build the request without sending it, for the first server to try.
+    /// See [http](crate::http).
+    pub fn http_request(&mut self) -> Result<HttpRequest> {
+        self.inner.http_request()
+    }
+
+    /// Make a request with the given parameters.
+    pub fn request(&mut self) -> Result<AuthenticateResponse> {
+        block_on(self.inner.request())
+    }
+}
+
+/// Blocking [RefreshBuilder](crate::refresh::RefreshBuilder)
+#[derive(Default)]
+pub struct RefreshBuilder<'a> {
+    inner: crate::refresh::RefreshBuilder<'a>,
+}
+
+impl<'a> RefreshBuilder<'a> {
+    pub fn new() -> RefreshBuilder<'a> {
+        RefreshBuilder::default()
+    }
+
+    /// Set client token, required
+    pub fn client_token(&mut self, client_token: Uuid) -> &mut RefreshBuilder<'a> {
+        self.inner.client_token(client_token);
+        self
+    }
+
+    /// Request the user object in the response.
+    pub fn request_user(&mut self) -> &mut RefreshBuilder<'a> {
+        self.inner.request_user();
+        self
+    }
+
+    /// Set access token, required
+    pub fn access_token(&mut self, access_token: &'a str) -> &mut RefreshBuilder<'a> {
+        self.inner.access_token(access_token);
+        self
+    }
+
+    /// Set authentication server.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut RefreshBuilder<'a>> {
+        self.inner.server(server)?;
+        Ok(self)
+    }
+
+    /// Set mirrored authentication servers to fail over between.
+    pub fn servers(&mut self, servers: &ServerPool) -> &mut RefreshBuilder<'a> {
+        self.inner.servers(servers);
+        self
+    }
+
+    /// Set endpoint, default is `/refresh`.
+    pub fn endpoint(&mut self, endpoint: &'a str) -> &mut RefreshBuilder<'a> {
+        self.inner.endpoint(endpoint);
+        self
+    }
+
+    /// Set client which sends the request.
+    pub fn client(&mut self, client: &Client) -> &mut RefreshBuilder<'a> {
+        self.inner.client(client);
+        self
+    }
+
+    /// Get the server which answered the last request.
+    pub fn answered_server(&self) -> Option<&Url> {
+        self.inner.answered_server()
+    }
+
+    /// Build the request without sending it, for the first server to try.
+    /// See [http](crate::http).
+    pub fn http_request(&mut self) -> Result<HttpRequest> {
+        self.inner.http_request()
+    }
+
+    /// Make a request with the given parameters.
+    pub fn request(&mut self) -> Result<RefreshResponse> {
+        block_on(self.inner.request())
+    }
+}
+
+/// Blocking [ValidateBuilder](crate::validate::ValidateBuilder)
+#[derive(Default)]
+pub struct ValidateBuilder<'a> {
+    inner: crate::validate::ValidateBuilder<'a>,
+}
+
+impl<'a> ValidateBuilder<'a> {
+    pub fn new() -> ValidateBuilder<'a> {
+        ValidateBuilder::default()
+    }
+
+    /// Set client token.
+    pub fn client_token(&mut self, client_token: Uuid) -> &mut ValidateBuilder<'a> {
+        self.inner.client_token(client_token);
+        self
+    }
+
+    /// Set access token, required
+    pub fn access_token(&mut self, access_token: &'a str) -> &mut ValidateBuilder<'a> {
+        self.inner.access_token(access_token);
+        self
+    }
+
+    /// Set authentication server.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut ValidateBuilder<'a>> {
+        self.inner.server(server)?;
+        Ok(self)
+    }
+
+    /// Set mirrored authentication servers to fail over between.
+    pub fn servers(&mut self, servers: &ServerPool) -> &mut ValidateBuilder<'a> {
+        self.inner.servers(servers);
+        self
+    }
+
+    /// Set endpoint, default is `/validate`.
+    pub fn endpoint(&mut self, endpoint: &'a str) -> &mut ValidateBuilder<'a> {
+        self.inner.endpoint(endpoint);
+        self
+    }
+
+    /// Set client which sends the request.
+    pub fn client(&mut self, client: &Client) -> &mut ValidateBuilder<'a> {
+        self.inner.client(client);
+        self
+    }
+
+    /// Get the server which answered the last request.
+    pub fn answered_server(&self) -> Option<&Url> {
+        self.inner.answered_server()
+    }
+
+    /// Build the request without sending it, for the first server to try.
+    /// See [http](crate::http).
+    pub fn http_request(&mut self) -> Result<HttpRequest> {
+        self.inner.http_request()
+    }
+
+    /// Make a request with the given parameters.
+    pub fn request(&mut self) -> Result<()> {
+        block_on(self.inner.request())
+    }
+}
+
+/// Blocking [InvalidateBuilder](crate::invalidate::InvalidateBuilder)
+#[derive(Default)]
+pub struct InvalidateBuilder<'a> {
+    inner: crate::invalidate::InvalidateBuilder<'a>,
+}
+
+impl<'a> InvalidateBuilder<'a> {
+    pub fn new() -> InvalidateBuilder<'a> {
+        InvalidateBuilder::default()
+    }
+
+    /// Set client token, required
+    pub fn client_token(&mut self, client_token: Uuid) -> &mut InvalidateBuilder<'a> {
+        self.inner.client_token(client_token);
+        self
+    }
+
+    /// Set access token, required
+    pub fn access_token(&mut self, access_token: &'a str) -> &mut InvalidateBuilder<'a> {
+        self.inner.access_token(access_token);
+        self
+    }
+
+    /// Set authentication server.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut InvalidateBuilder<'a>> {
+        self.inner.server(server)?;
+        Ok(self)
+    }
+
+    /// Set mirrored authentication servers to fail over between.
+    pub fn servers(&mut self, servers: &ServerPool) -> &mut InvalidateBuilder<'a> {
+        self.inner.servers(servers);
+        self
+    }
+
+    /// Set endpoint, default is `/invalidate`.
+    pub fn endpoint(&mut self, endpoint: &'a str) -> &mut InvalidateBuilder<'a> {
+        self.inner.endpoint(endpoint);
+        self
+    }
+
+    /// Set client which sends the request.
+    pub fn client(&mut self, client: &Client) -> &mut InvalidateBuilder<'a> {
+        self.inner.client(client);
+        self
+    }
+
+    /// Get the server which answered the last request.
+    pub fn answered_server(&self) -> Option<&Url> {
+        self.inner.answered_server()
+    }
+
+    /// Build the request without sending it, for the first server to try.
+    /// See [http](crate::http).
+    pub fn http_request(&mut self) -> Result<HttpRequest> {
+        self.inner.http_request()
+    }
+
+    /// Make a request with the given parameters.
+    pub fn request(&mut self) -> Result<()> {
+        block_on(self.inner.request())
+    }
+}
+
+/// Blocking [SignoutBuilder](crate::signout::SignoutBuilder)
+#[derive(Default)]
+pub struct SignoutBuilder<'a> {
+    inner: crate::signout::SignoutBuilder<'a>,
+}
+
+impl<'a> SignoutBuilder<'a> {
+    pub fn new() -> SignoutBuilder<'a> {
+        SignoutBuilder::default()
+    }
+
+    /// Set username, required
+    pub fn username(&mut self, username: &'a str) -> &mut SignoutBuilder<'a> {
+        self.inner.username(username);
+        self
+    }
+
+    /// Set password, required
+    pub fn password(&mut self, password: &'a str) -> &mut SignoutBuilder<'a> {
+        self.inner.password(password);
+        self
+    }
+
+    /// Set authentication server.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut SignoutBuilder<'a>> {
+        self.inner.server(server)?;
+        Ok(self)
+    }
+
+    /// Set mirrored authentication servers to fail over between.
+    pub fn servers(&mut self, servers: &ServerPool) -> &mut SignoutBuilder<'a> {
+        self.inner.servers(servers);
+        self
+    }
+
+    /// Set endpoint, default is `/signout`.
+    pub fn endpoint(&mut self, endpoint: &'a str) -> &mut SignoutBuilder<'a> {
+        self.inner.endpoint(endpoint);
+        self
+    }
+
+    /// Set client which sends the request.
+    pub fn client(&mut self, client: &Client) -> &mut SignoutBuilder<'a> {
+        self.inner.client(client);
+        self
+    }
+
+    /// Get the server which answered the last request.
+    pub fn answered_server(&self) -> Option<&Url> {
+        self.inner.answered_server()
+    }
+
+    /// Build the request without sending it, for the first server to try.
+    /// See [http](crate::http).
+    pub fn http_request(&mut self) -> Result<HttpRequest> {
+        self.inner.http_request()
+    }
+
+    /// Make a request with the given parameters.
+    pub fn request(&mut self) -> Result<()> {
+        block_on(self.inner.request())
+    }
+}
+
+/// Blocking [Session](crate::session::Session)
+///
+/// It converts from and into the async session, so that a session can be
+/// created on one side and used on the other.
+#[derive(Debug)]
+pub struct Session {
+    inner: crate::session::Session,
+}
+
+impl From<crate::session::Session> for Session {
+    fn from(inner: crate::session::Session) -> Session {
+        Session { inner }
+    }
+}
+
+impl From<Session> for crate::session::Session {
+    fn from(session: Session) -> crate::session::Session {
+        session.inner
+    }
+}
+
+impl Session {
+    /// Use saved `access_token` to create a session.
+    pub fn from_token<S: Into<String>>(access_token: S, client_token: Uuid) -> Result<Session> {
+        block_on(crate::session::Session::from_token(
+            access_token,
+            client_token,
+        ))
+        .map(Session::from)
+    }
+
+    /// Use saved `access_token` to create a session, which sends its
+    /// requests with `client`.
+    pub fn from_token_with_client<S: Into<String>>(
+        client: &Client,
+        access_token: S,
+        client_token: Uuid,
+    ) -> Result<Session> {
+        block_on(crate::session::Session::from_token_with_client(
+            client,
+            access_token,
+            client_token,
+        ))
+        .map(Session::from)
+    }
+
+    /// Use saved `access_token` to create a session on the first server of
+    /// `pool` which answers.
+    pub fn from_token_with_pool<S: Into<String>>(
+        client: &Client,
+        pool: &ServerPool,
+        access_token: S,
+        client_token: Uuid,
+    ) -> Result<Session> {
+        block_on(crate::session::Session::from_token_with_pool(
+            client,
+            pool,
+            access_token,
+            client_token,
+        ))
+        .map(Session::from)
+    }
+
+    /// Login to create a session.
+    pub fn login(username: &str, password: &str) -> Result<Session> {
+        block_on(crate::session::Session::login(username, password)).map(Session::from)
+    }
+
+    /// Login to create a session, which sends its requests with `client`.
+    pub fn login_with_client(client: &Client, username: &str, password: &str) -> Result<Session> {
+        block_on(crate::session::Session::login_with_client(
+            client, username, password,
+        ))
+        .map(Session::from)
+    }
+
+    /// Login on the first server of `pool` which answers.
+    pub fn login_with_pool(
+        client: &Client,
+        pool: &ServerPool,
+        username: &str,
+        password: &str,
+    ) -> Result<Session> {
+        block_on(crate::session::Session::login_with_pool(
+            client, pool, username, password,
+        ))
+        .map(Session::from)
+    }
+
+    /// Check that the `access_token` is still valid.
+    pub fn validate(&self) -> Result<()> {
+        block_on(self.inner.validate())
+    }
+
+    /// Replace the `access_token` with a new one.
+    pub fn refresh(&mut self) -> Result<()> {
+        block_on(self.inner.refresh())
+    }
+
+    /// Get the authentication server which the session is on.
+    pub fn auth_server(&self) -> &Url {
+        self.inner.auth_server()
+    }
+
+    /// Set session server, default is `https://sessionserver.mojang.com`.
+    pub fn session_server<T: IntoUrl>(&mut self, server: T) -> Result<&mut Session> {
+        self.inner.session_server(server)?;
+        Ok(self)
+    }
+
+    /// Set services server of [services](Session::services), default is
+    /// `https://api.minecraftservices.com`.
+    pub fn services_server<T: IntoUrl>(&mut self, server: T) -> Result<&mut Session> {
+        self.inner.services_server(server)?;
+        Ok(self)
+    }
+
+    /// Post a join message to Mojang session server.
+    pub fn join_server(&self, server_id_hash: &str) -> Result<()> {
+        block_on(self.inner.join_server(server_id_hash))
+    }
+
+    /// Build the request of [join_server](Session::join_server) without
+    /// sending it.
+    pub fn join_request(&self, server_id_hash: &str) -> Result<HttpRequest> {
+        self.inner.join_request(server_id_hash)
+    }
+
+    /// Get the chat signing key pair.
+    pub fn player_certificates(&mut self) -> Result<&PlayerCertificates> {
+        block_on(self.inner.player_certificates())
+    }
+
+    /// Create the Minecraft profile of an account which owns the game but
+    /// has no profile yet, and select it.
+    pub fn create_profile(&mut self, name: &str) -> Result<&Profile> {
+        block_on(self.inner.create_profile(name))
+    }
+
+    /// Get `access_token`
+    pub fn access_token(&self) -> &str {
+        self.inner.access_token()
+    }
+
+    /// Get `client_token`
+    pub fn client_token(&self) -> Uuid {
+        self.inner.client_token()
+    }
+
+    /// Get the selected profile, absent if the account has no profile.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.inner.profile()
+    }
+
+    /// Check whether the account can play.
+    pub fn account_state(&self) -> Result<AccountState> {
+        block_on(self.inner.account_state())
+    }
+
+    /// Create a [ServicesClient] authorized by the `access_token` of the
+    /// session.
+    pub fn services(&self) -> ServicesClient {
+        self.inner.services().into()
+    }
+
+    /// Get a client for the Realms API, playing `version` of the game.
+    pub fn realms(&self, version: &str) -> Result<RealmsClient> {
+        self.inner.realms(version).map(RealmsClient::from)
+    }
+
+    /// Get the [LaunchArguments] of the selected profile, without making
+    /// a request.
+    pub fn launch_arguments(&self) -> Result<LaunchArguments> {
+        self.inner.launch_arguments()
+    }
+
+    /// Get the [LaunchArguments] of the account after checking its
+    /// [account_state](Session::account_state).
+    pub fn launch_arguments_checked(&self) -> Result<LaunchArguments> {
+        block_on(self.inner.launch_arguments_checked())
+    }
+}
+
+/// Blocking [ServicesClient](crate::services::ServicesClient)
+pub struct ServicesClient {
+    inner: crate::services::ServicesClient,
+}
+
+impl From<crate::services::ServicesClient> for ServicesClient {
+    fn from(inner: crate::services::ServicesClient) -> ServicesClient {
+        ServicesClient { inner }
+    }
+}
+
+impl From<ServicesClient> for crate::services::ServicesClient {
+    fn from(client: ServicesClient) -> crate::services::ServicesClient {
+        client.inner
+    }
+}
+
+impl ServicesClient {
+    /// Create a client authorized by `access_token`.
+    pub fn new<S: Into<String>>(access_token: S) -> ServicesClient {
+        crate::services::ServicesClient::new(access_token).into()
+    }
+
+    /// Set base url, default is `https://api.minecraftservices.com`.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut ServicesClient> {
+        self.inner.server(server)?;
+        Ok(self)
+    }
+
+    /// Set client which sends the requests, default is [`Client::default`].
+    pub fn client(&mut self, client: &Client) -> &mut ServicesClient {
+        self.inner.client(client);
+        self
+    }
+
+    /// Get the Minecraft profile of the account.
+    pub fn profile(&self) -> Result<MinecraftProfile> {
+        block_on(self.inner.profile())
+    }
+
+    /// Upload a skin PNG, which must be 64x64 or the legacy 64x32.
+    pub fn upload_skin(&self, png: Vec<u8>, variant: SkinVariant) -> Result<MinecraftProfile> {
+        block_on(self.inner.upload_skin(png, variant))
+    }
+
+    /// Use the skin at `url`.
+    pub fn set_skin_url(&self, url: &str, variant: SkinVariant) -> Result<MinecraftProfile> {
+        block_on(self.inner.set_skin_url(url, variant))
+    }
+
+    /// Reset to the default skin.
+    pub fn reset_skin(&self) -> Result<MinecraftProfile> {
+        block_on(self.inner.reset_skin())
+    }
+
+    /// List the owned capes.
+    pub fn capes(&self) -> Result<Vec<Cape>> {
+        block_on(self.inner.capes())
+    }
+
+    /// Show the cape with the given `cape_id`, see [`Cape::id`].
+    pub fn equip_cape(&self, cape_id: &str) -> Result<MinecraftProfile> {
+        block_on(self.inner.equip_cape(cape_id))
+    }
+
+    /// Hide the cape in use.
+    pub fn hide_cape(&self) -> Result<MinecraftProfile> {
+        block_on(self.inner.hide_cape())
+    }
+
+    /// Get when the name was changed and whether it can be changed now.
+    pub fn name_change_info(&self) -> Result<NameChangeInfo> {
+        block_on(self.inner.name_change_info())
+    }
+
+    /// Check whether `name` can be used.
+    pub fn name_availability(&self, name: &str) -> Result<NameAvailability> {
+        block_on(self.inner.name_availability(name))
+    }
+
+    /// Change the profile name to `name`.
+    pub fn change_name(&self, name: &str) -> Result<MinecraftProfile> {
+        block_on(self.inner.change_name(name))
+    }
+
+    /// Create the Minecraft profile of an account which owns the game but
+    /// has no profile yet.
+    pub fn create_profile(&self, name: &str) -> Result<MinecraftProfile> {
+        block_on(self.inner.create_profile(name))
+    }
+
+    /// Request a new chat signing key pair.
+    pub fn player_certificates(&self) -> Result<PlayerCertificates> {
+        block_on(self.inner.player_certificates())
+    }
+
+    /// Get the owned products from `/entitlements/mcstore`.
+    pub fn entitlements(&self) -> Result<Entitlements> {
+        block_on(self.inner.entitlements())
+    }
+
+    /// Get the owned products and how they were obtained from
+    /// `/entitlements/license`.
+    pub fn license(&self) -> Result<Entitlements> {
+        block_on(self.inner.license())
+    }
+
+    /// Get the privileges, profanity filter preferences and ban status of
+    /// the player.
+    pub fn player_attributes(&self) -> Result<PlayerAttributes> {
+        block_on(self.inner.player_attributes())
+    }
+
+    /// Turn the profanity filter on or off.
+    pub fn set_profanity_filter(&self, profanity_filter_on: bool) -> Result<PlayerAttributes> {
+        block_on(self.inner.set_profanity_filter(profanity_filter_on))
+    }
+
+    /// Submit an abuse report.
+    pub fn report(&self, report: &AbuseReport) -> Result<()> {
+        block_on(self.inner.report(report))
+    }
+}
+
+/// Blocking [RealmsClient](crate::realms::RealmsClient)
+pub struct RealmsClient {
+    inner: crate::realms::RealmsClient,
+}
+
+impl From<crate::realms::RealmsClient> for RealmsClient {
+    fn from(inner: crate::realms::RealmsClient) -> RealmsClient {
+        RealmsClient { inner }
+    }
+}
+
+impl From<RealmsClient> for crate::realms::RealmsClient {
+    fn from(client: RealmsClient) -> crate::realms::RealmsClient {
+        client.inner
+    }
+}
+
+impl RealmsClient {
+    /// Create a client for the player with `profile_id` and `profile_name`,
+    /// playing `version` of the game, such as `1.20.4`.
+    pub fn new(
+        access_token: &str,
+        profile_id: Uuid,
+        profile_name: &str,
+        version: &str,
+    ) -> RealmsClient {
+        crate::realms::RealmsClient::new(access_token, profile_id, profile_name, version).into()
+    }
+
+    /// Set base url, default is `https://pc.realms.minecraft.net`.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut RealmsClient> {
+        self.inner.server(server)?;
+        Ok(self)
+    }
+
+    /// Set client which sends the requests, default is [`Client::default`].
+    pub fn client(&mut self, client: &Client) -> &mut RealmsClient {
+        self.inner.client(client);
+        self
+    }
+
+    /// Set how many times [join_address](RealmsClient::join_address) asks
+    /// while the realm is starting, default is `20`.
+    pub fn join_attempts(&mut self, join_attempts: u32) -> &mut RealmsClient {
+        self.inner.join_attempts(join_attempts);
+        self
+    }
+
+    /// List the realms which the player owns or is a member of.
+    pub fn worlds(&self) -> Result<Vec<RealmsWorld>> {
+        block_on(self.inner.worlds())
+    }
+
+    /// Get the address of the realm with `world_id`, starting it if needed.
+    pub fn join_address(&self, world_id: i64) -> Result<RealmsAddress> {
+        block_on(self.inner.join_address(world_id))
+    }
+
+    /// Whether Realms is available to the player, such as in their country.
+    pub fn is_available(&self) -> Result<bool> {
+        block_on(self.inner.is_available())
+    }
+
+    /// Check whether the client version can play on Realms.
+    pub fn compatibility(&self) -> Result<Compatibility> {
+        block_on(self.inner.compatibility())
+    }
+
+    /// Get the number of pending invitations.
+    pub fn pending_invites_count(&self) -> Result<u32> {
+        block_on(self.inner.pending_invites_count())
+    }
+
+    /// List the pending invitations.
+    pub fn pending_invites(&self) -> Result<Vec<PendingInvite>> {
+        block_on(self.inner.pending_invites())
+    }
+
+    /// Accept the invitation with `invitation_id`.
+    pub fn accept_invite(&self, invitation_id: &str) -> Result<()> {
+        block_on(self.inner.accept_invite(invitation_id))
+    }
+
+    /// Reject the invitation with `invitation_id`.
+    pub fn reject_invite(&self, invitation_id: &str) -> Result<()> {
+        block_on(self.inner.reject_invite(invitation_id))
+    }
+}
+
+/// Blocking [BlockedServersClient](crate::blocked_servers::BlockedServersClient)
+#[derive(Default)]
+pub struct BlockedServersClient {
+    inner: crate::blocked_servers::BlockedServersClient,
+}
+
+impl BlockedServersClient {
+    pub fn new() -> BlockedServersClient {
+        BlockedServersClient::default()
+    }
+
+    /// Set base url, default is `https://sessionserver.mojang.com`.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut BlockedServersClient> {
+        self.inner.server(server)?;
+        Ok(self)
+    }
+
+    /// set endpoint, default is `/blockedservers`.
+    pub fn endpoint<S: Into<String>>(&mut self, endpoint: S) -> &mut BlockedServersClient {
+        self.inner.endpoint(endpoint);
+        self
+    }
+
+    /// Set how long the list is cached, default is one hour.
+    pub fn cache_ttl(&mut self, cache_ttl: StdDuration) -> &mut BlockedServersClient {
+        self.inner.cache_ttl(cache_ttl);
+        self
+    }
+
+    /// Set client which sends the requests, default is [`Client::default`].
+    pub fn client(&mut self, client: &Client) -> &mut BlockedServersClient {
+        self.inner.client(client);
+        self
+    }
+
+    /// Get the list, downloading it if the cache is empty or outdated.
+    pub fn blocked_servers(&self) -> Result<Arc<BlockedServers>> {
+        block_on(self.inner.blocked_servers())
+    }
+
+    /// Whether the server at `host` is blocked, see [BlockedServers::is_blocked].
+    pub fn is_blocked(&self, host: &str) -> Result<bool> {
+        block_on(self.inner.is_blocked(host))
+    }
+}
+
+/// Blocking [ProfileKeyVerifier](crate::profile_key::ProfileKeyVerifier)
+#[derive(Default)]
+pub struct ProfileKeyVerifier {
+    inner: crate::profile_key::ProfileKeyVerifier,
+}
+
+impl ProfileKeyVerifier {
+    pub fn new() -> ProfileKeyVerifier {
+        ProfileKeyVerifier::default()
+    }
+
+    /// Set base url, default is `https://api.minecraftservices.com`.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut ProfileKeyVerifier> {
+        self.inner.server(server)?;
+        Ok(self)
+    }
+
+    /// set endpoint, default is `/publickeys`.
+    pub fn endpoint<S: Into<String>>(&mut self, endpoint: S) -> &mut ProfileKeyVerifier {
+        self.inner.endpoint(endpoint);
+        self
+    }
+
+    /// Set how long fetched keys are cached, default is one day.
+    pub fn cache_ttl(&mut self, cache_ttl: StdDuration) -> &mut ProfileKeyVerifier {
+        self.inner.cache_ttl(cache_ttl);
+        self
+    }
+
+    /// Set how long keys are accepted after they expire, default is zero.
+    pub fn clock_skew(&mut self, clock_skew: Duration) -> &mut ProfileKeyVerifier {
+        self.inner.clock_skew(clock_skew);
+        self
+    }
+
+    /// Set client which sends the requests, default is [`Client::default`].
+    pub fn client(&mut self, client: &Client) -> &mut ProfileKeyVerifier {
+        self.inner.client(client);
+        self
+    }
+
+    /// Get Mojang's keys, fetching them if the cache is empty or outdated.
+    pub fn keys(&self) -> Result<Arc<ServicesKeys>> {
+        block_on(self.inner.keys())
+    }
+
+    /// Verify a key sent by the player with `profile_id`, as sent since 1.19.1.
+    pub fn verify(&self, profile_id: Uuid, key: &ProfilePublicKey) -> Result<PublicKey> {
+        block_on(self.inner.verify(profile_id, key))
+    }
+
+    /// Verify a key in the form of 1.19.0.
+    pub fn verify_legacy(&self, key: &ProfilePublicKey) -> Result<PublicKey> {
+        block_on(self.inner.verify_legacy(key))
+    }
+}
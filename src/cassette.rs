@@ -0,0 +1,335 @@
+//! Recorded HTTP exchanges for regression tests
+//!
+//! A [Cassette] is a list of requests and their responses, saved as JSON.
+//! [RecordingTransport] records the exchanges of a real server into one,
+//! and [ReplayTransport] answers requests from one, so that the responses
+//! of third-party servers can be committed as fixtures and parsed offline.
+//!
+//! Secrets are redacted before they are recorded: the `Authorization`
+//! and cookie headers, and the values of [REDACTED_KEYS] in JSON bodies.
+//! Other bodies, such as skin uploads, are recorded as they are. Usernames
+//! and profiles are kept, so review a cassette before committing it.
+//!
+//! For example, to record:
+//! ```no_run
+//! # use sage_auth::auth::AuthenticateBuilder;
+//! # use sage_auth::cassette::RecordingTransport;
+//! # use sage_auth::client::Client;
+//! # use sage_auth::transport::ReqwestTransport;
+//! # use sage_auth::error::Result;
+//! # async fn anonymous() -> Result<()> {
+//! let recorder = RecordingTransport::new(ReqwestTransport::default());
+//! AuthenticateBuilder::new()
+//!     .client(&Client::with_transport(recorder.clone()))
+//!     .server("https://authserver.ely.by/auth/")?
+//!     .endpoint("authenticate")
+//!     .username("USERNAME")
+//!     .password("PASSWORD")
+//!     .request()
+//!     .await?;
+//!
+//! recorder.cassette().save("tests/cassettes/elyby.json").unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, COOKIE, SET_COOKIE};
+use reqwest::{StatusCode, Url};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::http::{HttpRequest, HttpResponse};
+use crate::transport::{Transport, TransportFuture};
+
+/// Replacement of redacted values.
+pub const REDACTED: &str = "REDACTED";
+
+/// Keys of JSON bodies whose values are redacted, at any depth.
+pub const REDACTED_KEYS: &[&str] = &["accessToken", "access_token", "password", "signature"];
+
+/// A list of recorded exchanges
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+/// A request and its response
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// A recorded request
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+
+    #[serde(flatten)]
+    pub body: RecordedBody,
+}
+
+/// A recorded response
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordedResponse {
+    pub status: u16,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+
+    #[serde(flatten)]
+    pub body: RecordedBody,
+}
+
+/// A recorded body, as JSON if it is valid JSON, and as text otherwise
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RecordedBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json: Option<Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl Cassette {
+    pub fn new() -> Cassette {
+        Cassette::default()
+    }
+
+    /// Read a cassette saved by [`Cassette::save`].
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Cassette> {
+        let content = fs::read(path)?;
+        Ok(serde_json::from_slice(&content)?)
+    }
+
+    /// Write the cassette as pretty-printed JSON.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut content = serde_json::to_vec_pretty(self)?;
+        content.push(b'\n');
+        fs::write(path, content)
+    }
+
+    /// Record an exchange, redacting its secrets.
+    pub fn record(&mut self, request: &HttpRequest, response: &HttpResponse) {
+        self.interactions.push(Interaction {
+            request: RecordedRequest {
+                method: request.method.to_string(),
+                url: request.url.to_string(),
+                headers: record_headers(&request.headers),
+                body: RecordedBody::new(&request.body),
+            },
+            response: RecordedResponse {
+                status: response.status.as_u16(),
+                headers: record_headers(&response.headers),
+                body: RecordedBody::new(&response.body),
+            },
+        });
+    }
+}
+
+impl RecordedRequest {
+    fn matches(&self, request: &HttpRequest) -> bool {
+        self.method == request.method.as_str()
+            && Url::parse(&self.url).is_ok_and(|url| {
+                url.path() == request.url.path() && url.query() == request.url.query()
+            })
+    }
+}
+
+impl RecordedResponse {
+    /// Rebuild the response, panicking if the status is invalid.
+    pub fn to_http(&self) -> HttpResponse {
+        let status = StatusCode::from_u16(self.status).expect("invalid recorded status");
+        let mut response = HttpResponse::new(status, self.body.to_bytes());
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                response.headers.insert(name, value);
+            }
+        }
+        response
+    }
+}
+
+impl RecordedBody {
+    fn new(body: &[u8]) -> RecordedBody {
+        if body.is_empty() {
+            return RecordedBody::default();
+        }
+        match serde_json::from_slice::<Value>(body) {
+            Ok(mut json) => {
+                redact(&mut json);
+                RecordedBody {
+                    json: Some(json),
+                    text: None,
+                }
+            }
+            Err(_) => RecordedBody {
+                json: None,
+                text: Some(String::from_utf8_lossy(body).into_owned()),
+            },
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match (&self.json, &self.text) {
+            (Some(json), _) => serde_json::to_vec(json).unwrap(),
+            (None, Some(text)) => text.clone().into_bytes(),
+            (None, None) => vec![],
+        }
+    }
+}
+
+fn record_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if name == AUTHORIZATION || name == COOKIE || name == SET_COOKIE {
+                REDACTED.to_owned()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.as_str().to_owned(), value)
+        })
+        .collect()
+}
+
+fn redact(json: &mut Value) {
+    match json {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if REDACTED_KEYS.contains(&key.as_str()) && value.is_string() {
+                    *value = Value::String(REDACTED.to_owned());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(array) => array.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// `RecordingTransport` records the exchanges of another transport
+///
+/// Clones share the cassette. Requests which fail without a response,
+/// such as timeouts, are not recorded.
+#[derive(Debug, Clone)]
+pub struct RecordingTransport<T> {
+    inner: T,
+    cassette: Arc<Mutex<Cassette>>,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    pub fn new(inner: T) -> RecordingTransport<T> {
+        RecordingTransport {
+            inner,
+            cassette: Arc::default(),
+        }
+    }
+
+    /// Get the exchanges recorded so far.
+    pub fn cassette(&self) -> Cassette {
+        self.cassette.lock().unwrap().clone()
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let response = self.inner.send(request.clone()).await?;
+            self.cassette.lock().unwrap().record(&request, &response);
+            Ok(response)
+        })
+    }
+}
+
+/// `ReplayTransport` answers requests from a cassette
+///
+/// A request is answered by the first interaction not yet replayed with
+/// the same method, path and query, regardless of the server. Requests
+/// without one are answered `404 Not Found`.
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::auth::AuthenticateBuilder;
+/// # use sage_auth::cassette::ReplayTransport;
+/// # use sage_auth::client::Client;
+/// # use sage_auth::error::Result;
+/// # async fn anonymous() -> Result<()> {
+/// let replay = ReplayTransport::load("tests/cassettes/elyby.json").unwrap();
+/// let resp = AuthenticateBuilder::new()
+///     .client(&Client::with_transport(replay))
+///     .server("https://authserver.ely.by/auth/")?
+///     .endpoint("authenticate")
+///     .username("USERNAME")
+///     .password("PASSWORD")
+///     .request()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ReplayTransport {
+    cassette: Cassette,
+    replayed: Mutex<HashSet<usize>>,
+}
+
+impl ReplayTransport {
+    pub fn new(cassette: Cassette) -> ReplayTransport {
+        ReplayTransport {
+            cassette,
+            replayed: Mutex::default(),
+        }
+    }
+
+    /// Replay the cassette saved at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<ReplayTransport> {
+        Ok(ReplayTransport::new(Cassette::load(path)?))
+    }
+
+    fn answer(&self, request: &HttpRequest) -> HttpResponse {
+        let mut replayed = self.replayed.lock().unwrap();
+        let found = self
+            .cassette
+            .interactions
+            .iter()
+            .enumerate()
+            .find(|(index, interaction)| {
+                !replayed.contains(index) && interaction.request.matches(request)
+            });
+        match found {
+            Some((index, interaction)) => {
+                replayed.insert(index);
+                interaction.response.to_http()
+            }
+            None => HttpResponse::new(
+                StatusCode::NOT_FOUND,
+                format!(
+                    "no recorded response for {} {}",
+                    request.method,
+                    request.url.path()
+                )
+                .into_bytes(),
+            ),
+        }
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let response = self.answer(&request);
+        Box::pin(async move { Ok(response) })
+    }
+}
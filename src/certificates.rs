@@ -0,0 +1,104 @@
+//! Player certificates
+//!
+//! The key pair which a player signs chat with, issued by the services API
+//! at `/player/certificates`. See [profile_key](crate::profile_key) for the
+//! form sent to servers.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde_derive::Deserialize;
+
+use crate::profile_key::ProfilePublicKey;
+use crate::signing::{pem_to_der, PublicKey, SigningKey};
+use crate::{Error, Result};
+
+/// A player's chat signing key pair, signed by Mojang
+#[derive(Clone, Debug)]
+pub struct PlayerCertificates {
+    /// Private key to sign chat with.
+    pub private_key: SigningKey,
+
+    pub public_key: PublicKey,
+
+    /// Mojang's signature of the public key, for 1.19.1 and later.
+    pub public_key_signature_v2: Vec<u8>,
+
+    /// Mojang's signature of the public key, for 1.19.0.
+    pub public_key_signature: Vec<u8>,
+
+    /// When the key pair stops being accepted.
+    pub expires_at: DateTime<Utc>,
+
+    /// When a new key pair should be requested.
+    pub refreshed_after: DateTime<Utc>,
+}
+
+/// Response body from Mojang server
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CertificatesResponse {
+    key_pair: KeyPair,
+    public_key_signature: String,
+    public_key_signature_v2: String,
+    expires_at: DateTime<Utc>,
+    refreshed_after: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyPair {
+    private_key: String,
+    public_key: String,
+}
+
+impl PlayerCertificates {
+    pub(crate) fn from_response(response: CertificatesResponse) -> Result<PlayerCertificates> {
+        let private_key = SigningKey::from_der(&pem_to_der(&response.key_pair.private_key)?)?;
+        let public_key = PublicKey::from_der(&pem_to_der(&response.key_pair.public_key)?)?;
+
+        Ok(PlayerCertificates {
+            private_key,
+            public_key,
+            public_key_signature_v2: decode_signature(&response.public_key_signature_v2)?,
+            public_key_signature: decode_signature(&response.public_key_signature)?,
+            expires_at: response.expires_at,
+            refreshed_after: response.refreshed_after,
+        })
+    }
+
+    /// Whether the key pair has expired.
+    pub fn is_expired(&self) -> bool {
+        Utc::now() >= self.expires_at
+    }
+
+    /// Whether a new key pair should be requested.
+    pub fn needs_refresh(&self) -> bool {
+        Utc::now() >= self.refreshed_after
+    }
+
+    /// Get the public key in the form of the chat session packet (1.19.3 and
+    /// later) and the login packet of 1.19.1 and 1.19.2.
+    pub fn public_key_data(&self) -> Result<ProfilePublicKey> {
+        self.profile_public_key(&self.public_key_signature_v2)
+    }
+
+    /// Get the public key in the form of the 1.19.0 login packet.
+    pub fn legacy_public_key_data(&self) -> Result<ProfilePublicKey> {
+        self.profile_public_key(&self.public_key_signature)
+    }
+
+    fn profile_public_key(&self, signature: &[u8]) -> Result<ProfilePublicKey> {
+        Ok(ProfilePublicKey {
+            expires_at: self.expires_at.timestamp_millis(),
+            key: self.public_key.to_der()?,
+            signature: signature.to_vec(),
+        })
+    }
+}
+
+fn decode_signature(signature: &str) -> Result<Vec<u8>> {
+    BASE64
+        .decode(signature)
+        .map_err(|_| Error::InvalidSignature)
+}
@@ -0,0 +1,145 @@
+//! Signed chat messages
+//!
+//! Since 1.19.3, chat messages are signed with `SHA256withRSA` by the key
+//! pair from [player certificates](crate::certificates), over the message
+//! link (sender, chat session and index) and body (salt, timestamp, content
+//! and the signatures of the last seen messages).
+
+use uuid::Uuid;
+
+use crate::certificates::PlayerCertificates;
+use crate::signing::{PublicKey, SigningKey};
+use crate::Result;
+
+/// A chat message and the data its signature covers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChatMessage {
+    /// Index of the message in the sender's chat session.
+    pub index: i32,
+
+    /// Profile id of the sender.
+    pub sender: Uuid,
+
+    /// Chat session id of the sender.
+    pub session_id: Uuid,
+
+    pub salt: i64,
+
+    /// Milliseconds since the Unix epoch, as sent in the chat packet.
+    pub timestamp: i64,
+
+    pub message: String,
+
+    /// Signatures of the messages the sender acknowledged.
+    pub last_seen: Vec<Vec<u8>>,
+}
+
+/// `ChatSession` signs the outgoing messages of a player
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::chat::ChatSession;
+/// # use sage_auth::session::Session;
+/// # use sage_auth::error::Result;
+/// # async fn anonymous(salt: i64, timestamp: i64) -> Result<()> {
+/// let mut session = Session::login("USERNAME", "PASSWORD").await?;
+/// let sender = session.profile().expect("no profile").id;
+/// let mut chat = ChatSession::new(sender, session.player_certificates().await?);
+///
+/// // Send `chat.session_id()` and the public key in the chat session packet, then:
+/// let (message, signature) = chat.sign("Hello", salt, timestamp, vec![]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct ChatSession {
+    sender: Uuid,
+    session_id: Uuid,
+    index: i32,
+    key: SigningKey,
+}
+
+impl ChatMessage {
+    /// Get the data which is signed.
+    pub fn signed_body(&self) -> Vec<u8> {
+        let message = self.message.as_bytes();
+        let mut body = Vec::with_capacity(64 + message.len() + 256 * self.last_seen.len());
+
+        // Signature version
+        body.extend_from_slice(&1i32.to_be_bytes());
+
+        // Message link
+        body.extend_from_slice(self.sender.as_bytes());
+        body.extend_from_slice(self.session_id.as_bytes());
+        body.extend_from_slice(&self.index.to_be_bytes());
+
+        // Message body, with the timestamp in seconds
+        body.extend_from_slice(&self.salt.to_be_bytes());
+        body.extend_from_slice(&self.timestamp.div_euclid(1000).to_be_bytes());
+        body.extend_from_slice(&(message.len() as i32).to_be_bytes());
+        body.extend_from_slice(message);
+        body.extend_from_slice(&(self.last_seen.len() as i32).to_be_bytes());
+        for signature in &self.last_seen {
+            body.extend_from_slice(signature);
+        }
+
+        body
+    }
+
+    /// Sign the message with the sender's private key.
+    pub fn sign(&self, key: &SigningKey) -> Vec<u8> {
+        key.sign_sha256_bytes(&self.signed_body())
+    }
+
+    /// Verify a signature of the message with the sender's public key.
+    pub fn verify(&self, key: &PublicKey, signature: &[u8]) -> Result<()> {
+        key.verify_sha256_bytes(&self.signed_body(), signature)
+    }
+}
+
+impl ChatSession {
+    /// Start a chat session with a random id for the player `sender`.
+    pub fn new(sender: Uuid, certificates: &PlayerCertificates) -> ChatSession {
+        ChatSession::with_id(sender, Uuid::new_v4(), certificates.private_key.clone())
+    }
+
+    /// Resume a chat session with the given id and key.
+    pub fn with_id(sender: Uuid, session_id: Uuid, key: SigningKey) -> ChatSession {
+        ChatSession {
+            sender,
+            session_id,
+            index: 0,
+            key,
+        }
+    }
+
+    /// Get the id of the chat session, sent in the chat session packet.
+    pub fn session_id(&self) -> Uuid {
+        self.session_id
+    }
+
+    /// Sign the next message of the session.
+    ///
+    /// `timestamp` is in milliseconds since the Unix epoch and `last_seen`
+    /// holds the signatures of the acknowledged messages.
+    pub fn sign<S: Into<String>>(
+        &mut self,
+        message: S,
+        salt: i64,
+        timestamp: i64,
+        last_seen: Vec<Vec<u8>>,
+    ) -> (ChatMessage, Vec<u8>) {
+        let message = ChatMessage {
+            index: self.index,
+            sender: self.sender,
+            session_id: self.session_id,
+            salt,
+            timestamp,
+            message: message.into(),
+            last_seen,
+        };
+        self.index += 1;
+
+        let signature = message.sign(&self.key);
+        (message, signature)
+    }
+}
@@ -0,0 +1,338 @@
+//! HTTP client shared by requests
+//!
+//! Every request of the crate is sent through a [Client], which retries
+//! failed requests by its [RetryPolicy] and keeps within the quotas of its
+//! [RateLimiter]. Requests go out through its [Transport]. Builders and clients use
+//! [`Client::default`] unless another one is given with their `client()`
+//! setter.
+
+use lazy_static::lazy_static;
+use rand::rngs::OsRng;
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, StatusCode, Url};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::http::{HttpRequest, HttpResponse};
+use crate::rate_limit::RateLimiter;
+use crate::transport::{ReqwestTransport, Transport};
+use crate::{Error, Result};
+
+lazy_static! {
+    pub(crate) static ref DEFAULT_CLIENT: Client = Client::new();
+}
+
+/// `Client` sends requests and retries them when they fail
+///
+/// It is cheap to clone, clones share the connection pool.
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::auth::AuthenticateBuilder;
+/// # use sage_auth::client::{Client, RetryPolicy};
+/// # use sage_auth::error::Result;
+/// # async fn anonymous() -> Result<()> {
+/// let mut policy = RetryPolicy::new();
+/// policy
+///     .max_attempts(5)
+///     .on_retry(|event| eprintln!("retrying {} in {:?}", event.url, event.delay));
+///
+/// let mut client = Client::new();
+/// client.retry_policy(policy);
+///
+/// let resp = AuthenticateBuilder::new()
+///     .client(&client)
+///     .username("USERNAME")
+///     .password("PASSWORD")
+///     .request()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Client {
+    transport: Arc<dyn Transport>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+/// When and how often failed requests are retried
+///
+/// A request is retried when it could not connect, timed out, or was
+/// answered with `408`, `429`, `500`, `502`, `503` or `504`. Requests which
+/// are not idempotent, such as `/authenticate` which issues a new token,
+/// are only retried when the server did not process them: when it could
+/// not connect, or was answered with `429` or `503`.
+///
+/// The delay before each retry doubles from
+/// [base_delay](RetryPolicy::base_delay) up to
+/// [max_delay](RetryPolicy::max_delay), with a random half of it as jitter.
+/// A `Retry-After` header replaces the delay, and the request is not
+/// retried if it asks for longer than
+/// [max_retry_after](RetryPolicy::max_retry_after).
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    max_retry_after: Duration,
+    on_retry: Option<RetryHook>,
+    retry_unavailable: bool,
+}
+
+type RetryHook = Arc<dyn Fn(&RetryEvent) + Send + Sync>;
+
+/// A retry about to happen, see [`RetryPolicy::on_retry`]
+#[derive(Debug)]
+pub struct RetryEvent<'a> {
+    /// The attempt which failed, starting from `1`.
+    pub attempt: u32,
+
+    /// How long until the next attempt.
+    pub delay: Duration,
+
+    pub method: &'a Method,
+    pub url: &'a Url,
+
+    /// Status of the response, if there was one.
+    pub status: Option<StatusCode>,
+
+    /// Error of the request, if there was no response.
+    pub error: Option<&'a Error>,
+}
+
+impl Default for Client {
+    fn default() -> Client {
+        Client::with_http(reqwest::Client::new())
+    }
+}
+
+impl Client {
+    pub fn new() -> Client {
+        Client::default()
+    }
+
+    /// Create a client over a configured reqwest client, such as one with a
+    /// proxy or timeouts.
+    pub fn with_http(http: reqwest::Client) -> Client {
+        Client {
+            transport: Arc::new(ReqwestTransport::new(http)),
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: Arc::new(RateLimiter::default()),
+        }
+    }
+
+    /// Create a client which sends its requests through `transport`, such
+    /// as a [MockTransport](crate::transport::MockTransport).
+    pub fn with_transport<T: Transport + 'static>(transport: T) -> Client {
+        Client {
+            transport: Arc::new(transport),
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: Arc::new(RateLimiter::default()),
+        }
+    }
+
+    /// Set retry policy, default is [`RetryPolicy::default`].
+    pub fn retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut Client {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Set rate limiter, default is [`RateLimiter::default`]. It is shared
+    /// by the clones of the client made after.
+    pub fn rate_limiter(&mut self, rate_limiter: RateLimiter) -> &mut Client {
+        self.rate_limiter = Arc::new(rate_limiter);
+        self
+    }
+
+    /// Clone the client, without retrying `503 Service Unavailable` for
+    /// callers which wait for the service themselves.
+    pub(crate) fn without_unavailable_retries(&self) -> Client {
+        let mut client = self.clone();
+        client.retry_policy.retry_unavailable = false;
+        client
+    }
+
+    /// Send a request, which is idempotent if its method is.
+    pub(crate) async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let idempotent = is_idempotent(&request.method);
+        self.execute(request, idempotent).await
+    }
+
+    /// Send a request given as data. A `POST` request is `idempotent` if it
+    /// can be repeated safely, such as `/validate`.
+    pub(crate) async fn send_http(
+        &self,
+        request: HttpRequest,
+        idempotent: bool,
+    ) -> Result<HttpResponse> {
+        self.execute(request, idempotent).await
+    }
+
+    async fn execute(&self, request: HttpRequest, idempotent: bool) -> Result<HttpResponse> {
+        let policy = &self.retry_policy;
+        let mut attempt = 1;
+
+        loop {
+            self.rate_limiter.acquire(&request.url).await;
+            let last = attempt >= policy.max_attempts;
+
+            let (status, delay, error) = match self.transport.send(request.clone()).await {
+                Ok(response) => {
+                    let status = response.status;
+                    if last || !policy.should_retry_status(status, idempotent) {
+                        return Ok(response);
+                    }
+                    match policy.delay(attempt, retry_after(&response.headers)) {
+                        Some(delay) => (Some(status), delay, None),
+                        None => return Ok(response),
+                    }
+                }
+                Err(error) => {
+                    if last || !(error.is_connect() || (idempotent && error.is_timeout())) {
+                        return Err(error);
+                    }
+                    (None, policy.backoff(attempt), Some(error))
+                }
+            };
+
+            if let Some(on_retry) = &policy.on_retry {
+                on_retry(&RetryEvent {
+                    attempt,
+                    delay,
+                    method: &request.method,
+                    url: &request.url,
+                    status,
+                    error: error.as_ref(),
+                });
+            }
+            tokio::time::delay_for(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            max_retry_after: Duration::from_secs(60),
+            on_retry: None,
+            retry_unavailable: true,
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("max_retry_after", &self.max_retry_after)
+            .field("on_retry", &self.on_retry.is_some())
+            .finish()
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> RetryPolicy {
+        RetryPolicy::default()
+    }
+
+    /// Create a policy which never retries.
+    pub fn none() -> RetryPolicy {
+        let mut policy = RetryPolicy::default();
+        policy.max_attempts(1);
+        policy
+    }
+
+    /// Set how many times a request is sent at most, default is `3`.
+    pub fn max_attempts(&mut self, max_attempts: u32) -> &mut RetryPolicy {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set delay before the first retry, default is 500 milliseconds.
+    pub fn base_delay(&mut self, base_delay: Duration) -> &mut RetryPolicy {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Set longest delay between retries, default is 10 seconds.
+    pub fn max_delay(&mut self, max_delay: Duration) -> &mut RetryPolicy {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Set longest `Retry-After` which is waited for, default is 60 seconds.
+    pub fn max_retry_after(&mut self, max_retry_after: Duration) -> &mut RetryPolicy {
+        self.max_retry_after = max_retry_after;
+        self
+    }
+
+    /// Call `on_retry` before each retry, such as to log it.
+    pub fn on_retry<F>(&mut self, on_retry: F) -> &mut RetryPolicy
+    where
+        F: Fn(&RetryEvent) + Send + Sync + 'static,
+    {
+        self.on_retry = Some(Arc::new(on_retry));
+        self
+    }
+
+    /// Whether a response with `status` is retried.
+    pub fn should_retry_status(&self, status: StatusCode, idempotent: bool) -> bool {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => true,
+            StatusCode::SERVICE_UNAVAILABLE => self.retry_unavailable,
+            StatusCode::REQUEST_TIMEOUT
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::GATEWAY_TIMEOUT => idempotent,
+            _ => false,
+        }
+    }
+
+    /// Get the delay after the failed `attempt`, starting from `1`, without
+    /// `Retry-After`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let delay = self
+            .base_delay
+            .checked_mul(1 << exponent)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        let half = delay / 2;
+        let jitter = OsRng.gen_range(0..=half.as_millis() as u64);
+        half + Duration::from_millis(jitter)
+    }
+
+    /// Get the delay after the failed `attempt`, or `None` if `retry_after`
+    /// is too long to wait.
+    fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        match retry_after {
+            Some(retry_after) if retry_after > self.max_retry_after => None,
+            Some(retry_after) => Some(retry_after),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+    )
+}
+
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(crate::error::parse_retry_after)
+}
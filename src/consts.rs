@@ -5,4 +5,17 @@ use url::Url;
 
 lazy_static! {
     pub static ref DEFAULT_SERVER: Url = Url::parse("https://authserver.mojang.com").unwrap();
+    pub static ref DEFAULT_REALMS_SERVER: Url =
+        Url::parse("https://pc.realms.minecraft.net").unwrap();
+    pub static ref DEFAULT_SESSION_SERVER: Url =
+        Url::parse("https://sessionserver.mojang.com").unwrap();
+    pub static ref DEFAULT_SERVICES_SERVER: Url =
+        Url::parse("https://api.minecraftservices.com").unwrap();
 }
+
+/// `errorMessage` sent by Mojang when the username or password is wrong.
+pub const INVALID_CREDENTIALS_MESSAGE: &str = "Invalid credentials. Invalid username or password.";
+
+/// `errorMessage` sent by Mojang when there were too many login attempts
+/// recently. Note that the username and password may still be valid.
+pub const TOO_MANY_ATTEMPTS_MESSAGE: &str = "Invalid credentials.";
@@ -1,11 +1,15 @@
 //! API error and common error
 
-use reqwest::{Error as ReqwestError, Response};
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, InvalidHeaderValue, RETRY_AFTER};
+use reqwest::{Error as ReqwestError, StatusCode};
 use std::error::Error as StdError;
 use std::fmt;
 use std::result::Result as StdResult;
+use std::time::Duration;
 use url::ParseError;
 
+use crate::consts::TOO_MANY_ATTEMPTS_MESSAGE;
 use crate::types::ErrorMessage;
 
 pub type Result<T> = StdResult<T, Error>;
@@ -32,6 +36,65 @@ pub enum ApiError {
     },
 }
 
+/// Longest part of an error body kept in [ResponseInfo].
+pub const MAX_BODY_LENGTH: usize = 1024;
+
+/// Details of an error response
+#[derive(Debug, Clone)]
+pub struct ResponseInfo {
+    /// HTTP status code.
+    pub status: StatusCode,
+
+    /// How long to wait before retrying, from the `Retry-After` header.
+    pub retry_after: Option<Duration>,
+
+    /// `cause` from the message, such as `UserMigratedException`.
+    pub cause: Option<String>,
+
+    /// Raw body, truncated to [MAX_BODY_LENGTH] bytes.
+    pub body: String,
+}
+
+/// Reason why a profile name was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameError {
+    /// The name is not 3 to 16 characters of `[A-Za-z0-9_]`, or is not
+    /// allowed by the server.
+    Invalid,
+
+    /// The name is used by another profile.
+    Taken,
+
+    /// The name of the profile was changed too recently.
+    TooSoon,
+}
+
+/// Reason why a profile public key was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileKeyError {
+    /// The key is not a valid RSA public key.
+    InvalidKey,
+
+    /// The key was not signed by Mojang, or not for this profile.
+    InvalidSignature,
+
+    /// The key has expired.
+    Expired,
+}
+
+/// Reason why an abuse report was not submitted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportError {
+    /// The report is incomplete or exceeds the limits of the service.
+    Invalid(String),
+
+    /// Too many reports were submitted recently.
+    Throttled,
+
+    /// Reporting is disabled for the account or by the service.
+    Disabled,
+}
+
 /// Common errors
 #[derive(Debug)]
 pub enum Error {
@@ -41,11 +104,55 @@ pub enum Error {
     /// Url parse error.
     UrlParseError(ParseError),
 
+    /// A response body is not the expected JSON.
+    Json(serde_json::Error),
+
+    /// The request timed out, in a [Transport](crate::transport::Transport)
+    /// other than reqwest.
+    Timeout,
+
+    /// Could not connect to the server, in a
+    /// [Transport](crate::transport::Transport) other than reqwest.
+    Connect(String),
+
+    /// A header value, such as an access token, has characters which are not
+    /// allowed in headers.
+    InvalidHeader(InvalidHeaderValue),
+
     /// Missing required fields to generate a request.
     MissingField(&'static str),
 
     /// API error, from Mojang server
-    API(ApiError),
+    ///
+    /// The details of the response are missing when the error was made
+    /// locally rather than by a server.
+    API(ApiError, Option<ResponseInfo>),
+
+    /// Error response whose body is not an API error message, such as an
+    /// HTML page from a proxy or an empty body.
+    Http(ResponseInfo),
+
+    /// The image is not an acceptable skin.
+    InvalidSkin(String),
+
+    /// A profile name was refused.
+    Name(NameError),
+
+    /// A profile public key was rejected.
+    ProfileKey(ProfileKeyError),
+
+    /// An abuse report was not submitted.
+    Report(ReportError),
+
+    /// A key could not be generated, loaded or saved.
+    Key(String),
+
+    /// A signature does not match the signed data.
+    InvalidSignature,
+
+    /// A password could not be hashed.
+    #[cfg(feature = "server")]
+    PasswordHash(String),
 }
 
 impl fmt::Display for Error {
@@ -55,25 +162,58 @@ impl fmt::Display for Error {
             Error::UrlParseError(url_parse_error) => {
                 write!(f, "URL parse error: {}", url_parse_error)
             }
+            Error::Json(json_error) => write!(f, "JSON error: {}", json_error),
+            Error::Timeout => write!(f, "Request timed out"),
+            Error::Connect(message) => write!(f, "Connection error: {}", message),
+            Error::InvalidHeader(header_error) => write!(f, "Invalid header: {}", header_error),
             Error::MissingField(field) => write!(f, "Missing field: {}", field),
-            Error::API(api_error) => match api_error {
-                ApiError::MethodNotAllowed(message) => {
-                    write!(f, "API error: MethodNotAllowed ({})", message)
-                }
-                ApiError::NotFound(message) => write!(f, "API error: NotFound ({})", message),
-                ApiError::ForbiddenOperationException(message) => {
-                    write!(f, "API error: ForbiddenOperationException ({})", message)
-                }
-                ApiError::IllegalArgumentException(message) => {
-                    write!(f, "API error: IllegalArgumentException ({})", message)
+            Error::InvalidSkin(message) => write!(f, "Invalid skin: {}", message),
+            Error::Name(name_error) => match name_error {
+                NameError::Invalid => write!(f, "Name error: invalid name"),
+                NameError::Taken => write!(f, "Name error: name is taken"),
+                NameError::TooSoon => write!(f, "Name error: name was changed too recently"),
+            },
+            Error::ProfileKey(profile_key_error) => match profile_key_error {
+                ProfileKeyError::InvalidKey => write!(f, "Profile key error: invalid key"),
+                ProfileKeyError::InvalidSignature => {
+                    write!(f, "Profile key error: invalid signature")
                 }
-                ApiError::UnsupportedMediaType(message) => {
-                    write!(f, "API error: UnsupportedMediaType ({})", message)
+                ProfileKeyError::Expired => write!(f, "Profile key error: expired"),
+            },
+            Error::Report(report_error) => match report_error {
+                ReportError::Invalid(message) => write!(f, "Report error: {}", message),
+                ReportError::Throttled => write!(f, "Report error: too many reports"),
+                ReportError::Disabled => write!(f, "Report error: reporting is disabled"),
+            },
+            Error::Key(message) => write!(f, "Key error: {}", message),
+            Error::InvalidSignature => write!(f, "Invalid signature"),
+            #[cfg(feature = "server")]
+            Error::PasswordHash(message) => write!(f, "Password hash error: {}", message),
+            Error::API(api_error, info) => {
+                match api_error {
+                    ApiError::MethodNotAllowed(message) => {
+                        write!(f, "API error: MethodNotAllowed ({})", message)?
+                    }
+                    ApiError::NotFound(message) => write!(f, "API error: NotFound ({})", message)?,
+                    ApiError::ForbiddenOperationException(message) => {
+                        write!(f, "API error: ForbiddenOperationException ({})", message)?
+                    }
+                    ApiError::IllegalArgumentException(message) => {
+                        write!(f, "API error: IllegalArgumentException ({})", message)?
+                    }
+                    ApiError::UnsupportedMediaType(message) => {
+                        write!(f, "API error: UnsupportedMediaType ({})", message)?
+                    }
+                    ApiError::Unknown { error, message } => {
+                        write!(f, "API error: {} ({})", error, message)?
+                    }
                 }
-                ApiError::Unknown { error, message } => {
-                    write!(f, "API error: {} ({})", error, message)
+                match info.as_ref().and_then(|info| info.cause.as_ref()) {
+                    Some(cause) => write!(f, ", caused by {}", cause),
+                    None => Ok(()),
                 }
-            },
+            }
+            Error::Http(info) => write!(f, "HTTP error: {} ({})", info.status, info.body),
         }
     }
 }
@@ -83,6 +223,8 @@ impl StdError for Error {
         match self {
             Error::Reqwest(reqwest_error) => Some(reqwest_error),
             Error::UrlParseError(url_parse_error) => Some(url_parse_error),
+            Error::Json(json_error) => Some(json_error),
+            Error::InvalidHeader(header_error) => Some(header_error),
             _ => None,
         }
     }
@@ -100,29 +242,210 @@ impl From<ParseError> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Json(error)
+    }
+}
+
+impl From<InvalidHeaderValue> for Error {
+    fn from(error: InvalidHeaderValue) -> Self {
+        Error::InvalidHeader(error)
+    }
+}
+
 impl Error {
-    pub(crate) async fn from_response(error: Response) -> Self {
-        let msg = error.json::<ErrorMessage>().await;
-        if msg.is_err() {
-            return msg.unwrap_err().into();
-        }
-        let msg = msg.unwrap();
-
-        if msg.error == "ForbiddenOperationException" {
-            Error::API(ApiError::ForbiddenOperationException(msg.error_message))
-        } else if msg.error == "IllegalArgumentException" {
-            Error::API(ApiError::IllegalArgumentException(msg.error_message))
-        } else if msg.error == "Method Not Allowed" {
-            Error::API(ApiError::MethodNotAllowed(msg.error_message))
-        } else if msg.error == "Not Found" {
-            Error::API(ApiError::NotFound(msg.error_message))
-        } else if msg.error == "Unsupported Media Type" {
-            Error::API(ApiError::UnsupportedMediaType(msg.error_message))
-        } else {
-            Error::API(ApiError::Unknown {
-                error: msg.error,
-                message: msg.error_message,
-            })
+    /// Decode an error response from its status, headers and body.
+    ///
+    /// Bodies which are an API error message become [`Error::API`], any
+    /// other body becomes [`Error::Http`].
+    pub fn from_response_parts(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Self {
+        let message = serde_json::from_slice::<ErrorMessage>(body);
+        let mut info = ResponseInfo::new(status, headers, body);
+
+        match message {
+            Ok(message) => {
+                info.cause = message.cause;
+                Error::API(api_error(message.error, message.error_message), Some(info))
+            }
+            Err(_) => Error::Http(info),
+        }
+    }
+
+    /// Whether the username or password was wrong.
+    pub fn is_invalid_credentials(&self) -> bool {
+        match self.forbidden_message() {
+            Some(message) => {
+                !self.is_account_migrated()
+                    && !self.is_rate_limited()
+                    && (message.starts_with("invalid credentials")
+                        || message.contains("invalid username or password")
+                        || message.contains("incorrect username or password"))
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the access token is invalid or has expired, and the session
+    /// has to be refreshed or logged in again.
+    pub fn is_token_invalid(&self) -> bool {
+        if let Some(message) = self.forbidden_message() {
+            if message.starts_with("invalid token")
+                || message.starts_with("token does not exist")
+                || message.contains("token expired")
+            {
+                return true;
+            }
         }
+        self.status() == Some(StatusCode::UNAUTHORIZED)
     }
+
+    /// Whether the Mojang account was migrated and has to log in with its
+    /// email address, or with a Microsoft account.
+    pub fn is_account_migrated(&self) -> bool {
+        match self {
+            Error::API(api_error, info) => {
+                info.as_ref().and_then(|info| info.cause.as_deref())
+                    == Some("UserMigratedException")
+                    || api_message(api_error)
+                        .to_lowercase()
+                        .contains("account migrated")
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether too many requests were made recently.
+    ///
+    /// Mojang answers too many login attempts with the same error as wrong
+    /// credentials, but only [TOO_MANY_ATTEMPTS_MESSAGE]
+    /// as the message.
+    pub fn is_rate_limited(&self) -> bool {
+        if let Error::Report(ReportError::Throttled) = self {
+            return true;
+        }
+        if self.status() == Some(StatusCode::TOO_MANY_REQUESTS) {
+            return true;
+        }
+        match self.forbidden_message() {
+            Some(message) => {
+                message.eq_ignore_ascii_case(TOO_MANY_ATTEMPTS_MESSAGE)
+                    || message.contains("too many")
+                    || message.contains("rate limit")
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the request may succeed if it is made again later: it was
+    /// rate limited, timed out, could not connect, or the server failed.
+    pub fn is_retryable(&self) -> bool {
+        if self.is_rate_limited() {
+            return true;
+        }
+        if self.is_timeout() || self.is_connect() {
+            return true;
+        }
+        match self.status() {
+            Some(status) => {
+                status == StatusCode::REQUEST_TIMEOUT
+                    || (status.is_server_error() && status != StatusCode::NOT_IMPLEMENTED)
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the request timed out.
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            Error::Reqwest(error) => error.is_timeout(),
+            Error::Timeout => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the request could not connect to the server.
+    pub fn is_connect(&self) -> bool {
+        match self {
+            Error::Reqwest(error) => error.is_connect(),
+            Error::Connect(_) => true,
+            _ => false,
+        }
+    }
+
+    fn status(&self) -> Option<StatusCode> {
+        self.response_info().map(|info| info.status)
+    }
+
+    /// Get the lowercased message of a `ForbiddenOperationException`.
+    fn forbidden_message(&self) -> Option<String> {
+        match self {
+            Error::API(ApiError::ForbiddenOperationException(message), _) => {
+                Some(message.trim().to_lowercase())
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the details of the response, if the error is from one.
+    pub fn response_info(&self) -> Option<&ResponseInfo> {
+        match self {
+            Error::API(_, info) => info.as_ref(),
+            Error::Http(info) => Some(info),
+            _ => None,
+        }
+    }
+}
+
+impl ResponseInfo {
+    /// Collect the details of a response with `status`, `headers` and `body`.
+    pub fn new(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> ResponseInfo {
+        let end = body.len().min(MAX_BODY_LENGTH);
+        ResponseInfo {
+            status,
+            retry_after: headers
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after),
+            cause: None,
+            body: String::from_utf8_lossy(&body[..end]).into_owned(),
+        }
+    }
+}
+
+fn api_message(api_error: &ApiError) -> &str {
+    match api_error {
+        ApiError::MethodNotAllowed(message)
+        | ApiError::NotFound(message)
+        | ApiError::ForbiddenOperationException(message)
+        | ApiError::IllegalArgumentException(message)
+        | ApiError::UnsupportedMediaType(message)
+        | ApiError::Unknown { message, .. } => message,
+    }
+}
+
+fn api_error(error: String, message: String) -> ApiError {
+    match error.as_str() {
+        "ForbiddenOperationException" => ApiError::ForbiddenOperationException(message),
+        "IllegalArgumentException" => ApiError::IllegalArgumentException(message),
+        "Method Not Allowed" => ApiError::MethodNotAllowed(message),
+        "Not Found" => ApiError::NotFound(message),
+        "Unsupported Media Type" => ApiError::UnsupportedMediaType(message),
+        _ => ApiError::Unknown { error, message },
+    }
+}
+
+/// Parse `Retry-After`, either seconds or an HTTP date.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
 }
@@ -0,0 +1,131 @@
+//! HTTP requests and responses as plain data
+//!
+//! The authentication requests and `/session/minecraft/join` are split in
+//! two steps which do no I/O: building an [HttpRequest], and reading an
+//! [HttpResponse] into the result of the request. The builders send the
+//! requests through their [Client](crate::client::Client), other HTTP
+//! clients can send them instead.
+//!
+//! For example:
+//! ```no_run
+//! # use sage_auth::auth::{self, AuthenticateBuilder};
+//! # use sage_auth::http::{HttpRequest, HttpResponse};
+//! # use sage_auth::error::Result;
+//! # fn send(request: HttpRequest) -> HttpResponse { unimplemented!() }
+//! # fn anonymous() -> Result<()> {
+//! let request = AuthenticateBuilder::new()
+//!     .username("USERNAME")
+//!     .password("PASSWORD")
+//!     .http_request()?;
+//!
+//! let response = send(request);
+//! let resp = auth::parse_response(&response)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::{Method, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{Error, Result};
+
+/// A request to send
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+
+    /// Body, empty if there is none.
+    pub body: Vec<u8>,
+}
+
+/// A response which was received
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn new(method: Method, url: Url) -> HttpRequest {
+        HttpRequest {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: vec![],
+        }
+    }
+
+    /// Create a request with `body` as JSON.
+    pub fn json<T: Serialize>(method: Method, url: Url, body: &T) -> Result<HttpRequest> {
+        let mut request = HttpRequest::new(method, url);
+        request
+            .headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        request.body = serde_json::to_vec(body)?;
+        Ok(request)
+    }
+
+    /// Set the header `name` to `value`, returns [`Error::InvalidHeader`] if
+    /// `value` is not a valid header value.
+    pub fn header(&mut self, name: HeaderName, value: &str) -> Result<&mut HttpRequest> {
+        self.headers.insert(name, HeaderValue::from_str(value)?);
+        Ok(self)
+    }
+
+    pub(crate) fn into_reqwest(self, client: &reqwest::Client) -> reqwest::RequestBuilder {
+        client
+            .request(self.method, self.url)
+            .headers(self.headers)
+            .body(self.body)
+    }
+}
+
+impl HttpResponse {
+    pub fn new(status: StatusCode, body: Vec<u8>) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: HeaderMap::new(),
+            body,
+        }
+    }
+
+    /// Read the whole response of reqwest.
+    pub(crate) async fn read(response: Response) -> Result<HttpResponse> {
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?.to_vec();
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    /// Decode the body as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    /// Decode the body as text, replacing invalid UTF-8.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Get the error of a failed response, see [`Error::from_response_parts`].
+    pub fn error(&self) -> Error {
+        Error::from_response_parts(self.status, &self.headers, &self.body)
+    }
+
+    /// Succeed with `()` if the status is `204 No Content`, or get the error.
+    pub(crate) fn no_content(&self) -> Result<()> {
+        match self.status {
+            StatusCode::NO_CONTENT => Ok(()),
+            _ => Err(self.error()),
+        }
+    }
+}
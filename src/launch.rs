@@ -0,0 +1,356 @@
+//! Game launch arguments
+//!
+//! The version JSON of the game lists its arguments as templates, such as
+//! `--username ${auth_player_name}`, in `arguments.game` since 1.13 and in
+//! `minecraftArguments` before. [LaunchArguments] fills in the variables
+//! which come from the account, and any other variables of the launcher.
+
+use md5::{Digest, Md5};
+use serde_derive::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Kind of account, the `${user_type}` variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserType {
+    /// Mojang account.
+    Mojang,
+
+    /// Microsoft account.
+    Msa,
+
+    /// Offline or legacy account.
+    Legacy,
+}
+
+/// An entry of `arguments.game` or `arguments.jvm` in the version JSON
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Argument {
+    Plain(String),
+    Conditional {
+        rules: Vec<Rule>,
+        value: ArgumentValue,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ArgumentValue {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// A rule of a conditional argument
+#[derive(Deserialize, Debug, Clone)]
+pub struct Rule {
+    pub action: RuleAction,
+    #[serde(default)]
+    pub features: HashMap<String, bool>,
+    pub os: Option<OsRule>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Allow,
+    Disallow,
+}
+
+/// Operating system of a rule, `version` is a pattern of the OS version
+/// and is not checked.
+#[derive(Deserialize, Debug, Clone)]
+pub struct OsRule {
+    pub name: Option<String>,
+    pub arch: Option<String>,
+    pub version: Option<String>,
+}
+
+/// `LaunchArguments` expands the argument templates of the version JSON
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::launch::Argument;
+/// # use sage_auth::session::Session;
+/// # use sage_auth::error::Result;
+/// # async fn anonymous(arguments: Vec<Argument>) -> Result<()> {
+/// let session = Session::login("USERNAME", "PASSWORD").await?;
+/// let args = session
+///     .launch_arguments_checked()
+///     .await?
+///     .variable("version_name", "1.20.4")
+///     .variable("game_directory", "/home/steve/.minecraft")
+///     .game_arguments(&arguments);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LaunchArguments {
+    player_name: String,
+    uuid: Uuid,
+    access_token: String,
+    user_type: UserType,
+    user_properties: BTreeMap<String, Vec<String>>,
+    xuid: String,
+    client_id: String,
+    demo: bool,
+    features: HashMap<String, bool>,
+    variables: HashMap<String, String>,
+}
+
+impl UserType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UserType::Mojang => "mojang",
+            UserType::Msa => "msa",
+            UserType::Legacy => "legacy",
+        }
+    }
+}
+
+impl Argument {
+    /// Get the values of the argument, empty if its rules do not allow it
+    /// with `features`.
+    pub fn values(&self, features: &HashMap<String, bool>) -> Vec<&str> {
+        match self {
+            Argument::Plain(value) => vec![value],
+            Argument::Conditional { rules, value } => {
+                if !rules_allow(rules, features) {
+                    return vec![];
+                }
+                match value {
+                    ArgumentValue::One(value) => vec![value],
+                    ArgumentValue::Many(values) => values.iter().map(String::as_str).collect(),
+                }
+            }
+        }
+    }
+}
+
+/// Whether `rules` allow an argument: the last rule which applies decides,
+/// and nothing is allowed if none applies.
+pub fn rules_allow(rules: &[Rule], features: &HashMap<String, bool>) -> bool {
+    if rules.is_empty() {
+        return true;
+    }
+
+    let mut allowed = false;
+    for rule in rules {
+        if rule.applies(features) {
+            allowed = rule.action == RuleAction::Allow;
+        }
+    }
+    allowed
+}
+
+impl Rule {
+    fn applies(&self, features: &HashMap<String, bool>) -> bool {
+        let features_match = self
+            .features
+            .iter()
+            .all(|(name, &value)| features.get(name).copied().unwrap_or(false) == value);
+        let os_matches = self.os.as_ref().is_none_or(OsRule::matches);
+        features_match && os_matches
+    }
+}
+
+impl OsRule {
+    fn matches(&self) -> bool {
+        let name = match std::env::consts::OS {
+            "macos" => "osx",
+            other => other,
+        };
+        self.name.as_deref().is_none_or(|n| n == name)
+            && self
+                .arch
+                .as_deref()
+                .is_none_or(|arch| arch == std::env::consts::ARCH)
+    }
+}
+
+/// Get the UUID which the game gives an offline player: the MD5 of
+/// `OfflinePlayer:<name>` as a version 3 UUID.
+pub fn offline_uuid(name: &str) -> Uuid {
+    let mut hash: [u8; 16] = Md5::digest(format!("OfflinePlayer:{}", name).as_bytes()).into();
+    hash[6] = (hash[6] & 0x0f) | 0x30;
+    hash[8] = (hash[8] & 0x3f) | 0x80;
+    Uuid::from_bytes(hash)
+}
+
+impl LaunchArguments {
+    /// Create the arguments for the profile `player_name` with `uuid`,
+    /// authorized by `access_token`.
+    pub fn new<S, T>(player_name: S, uuid: Uuid, access_token: T) -> LaunchArguments
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        LaunchArguments {
+            player_name: player_name.into(),
+            uuid,
+            access_token: access_token.into(),
+            user_type: UserType::Mojang,
+            user_properties: BTreeMap::new(),
+            xuid: String::new(),
+            client_id: String::new(),
+            demo: false,
+            features: HashMap::new(),
+            variables: HashMap::new(),
+        }
+    }
+
+    /// Create the arguments for an offline player, which the game only
+    /// accepts on servers in offline mode.
+    pub fn offline<S: Into<String>>(player_name: S) -> LaunchArguments {
+        let player_name = player_name.into();
+        let uuid = offline_uuid(&player_name);
+        let mut arguments = LaunchArguments::new(player_name, uuid, "0");
+        arguments.user_type(UserType::Legacy);
+        arguments
+    }
+
+    /// Set user type, default is [UserType::Mojang].
+    pub fn user_type(&mut self, user_type: UserType) -> &mut LaunchArguments {
+        self.user_type = user_type;
+        self
+    }
+
+    /// Add a user property, such as `twitch_access_token`.
+    pub fn user_property<S, T>(&mut self, name: S, value: T) -> &mut LaunchArguments
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        self.user_properties
+            .entry(name.into())
+            .or_default()
+            .push(value.into());
+        self
+    }
+
+    /// Set Xbox user id of a Microsoft account, default is empty.
+    pub fn xuid<S: Into<String>>(&mut self, xuid: S) -> &mut LaunchArguments {
+        self.xuid = xuid.into();
+        self
+    }
+
+    /// Set client id of the launcher, default is empty.
+    pub fn client_id<S: Into<String>>(&mut self, client_id: S) -> &mut LaunchArguments {
+        self.client_id = client_id.into();
+        self
+    }
+
+    /// Set whether the game runs as the demo, the `is_demo_user` feature.
+    pub fn demo(&mut self, demo: bool) -> &mut LaunchArguments {
+        self.demo = demo;
+        self
+    }
+
+    /// Enable or disable a feature of the rules, such as
+    /// `has_custom_resolution`.
+    pub fn feature<S: Into<String>>(&mut self, name: S, enabled: bool) -> &mut LaunchArguments {
+        self.features.insert(name.into(), enabled);
+        self
+    }
+
+    /// Set a variable of the launcher, such as `version_name` or
+    /// `game_directory`.
+    pub fn variable<S, T>(&mut self, name: S, value: T) -> &mut LaunchArguments
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        self.variables.insert(name.into(), value.into());
+        self
+    }
+
+    /// Get the value of every variable, by name without `${}`.
+    pub fn substitutions(&self) -> HashMap<String, String> {
+        let uuid = self.uuid.to_simple().to_string();
+        let user_properties =
+            serde_json::to_string(&self.user_properties).unwrap_or_else(|_| "{}".to_owned());
+
+        let mut substitutions = self.variables.clone();
+        substitutions.extend(
+            vec![
+                ("auth_player_name", self.player_name.clone()),
+                ("auth_uuid", uuid.clone()),
+                ("auth_access_token", self.access_token.clone()),
+                (
+                    "auth_session",
+                    format!("token:{}:{}", self.access_token, uuid),
+                ),
+                ("user_type", self.user_type.as_str().to_owned()),
+                ("user_properties", user_properties),
+                ("auth_xuid", self.xuid.clone()),
+                ("clientid", self.client_id.clone()),
+            ]
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value)),
+        );
+        substitutions
+    }
+
+    /// Get the features of the rules, including `is_demo_user`.
+    pub fn features(&self) -> HashMap<String, bool> {
+        let mut features = self.features.clone();
+        features.insert("is_demo_user".to_owned(), self.demo);
+        features
+    }
+
+    /// Replace the variables in `template`, leaving unknown variables as is.
+    pub fn expand(&self, template: &str) -> String {
+        expand(template, &self.substitutions())
+    }
+
+    /// Expand `arguments.game` of the version JSON.
+    pub fn game_arguments(&self, arguments: &[Argument]) -> Vec<String> {
+        let substitutions = self.substitutions();
+        let features = self.features();
+        arguments
+            .iter()
+            .flat_map(|argument| argument.values(&features))
+            .map(|value| expand(value, &substitutions))
+            .collect()
+    }
+
+    /// Expand `minecraftArguments` of the version JSON before 1.13, adding
+    /// `--demo` for the demo.
+    pub fn legacy_game_arguments(&self, arguments: &str) -> Vec<String> {
+        let substitutions = self.substitutions();
+        let mut arguments: Vec<_> = arguments
+            .split_whitespace()
+            .map(|value| expand(value, &substitutions))
+            .collect();
+        if self.demo {
+            arguments.push("--demo".to_owned());
+        }
+        arguments
+    }
+}
+
+fn expand(template: &str, substitutions: &HashMap<String, String>) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let variable = &rest[start + 2..];
+        match variable.find('}') {
+            Some(end) => {
+                match substitutions.get(&variable[..end]) {
+                    Some(value) => expanded.push_str(value),
+                    None => expanded.push_str(&rest[start..start + end + 3]),
+                }
+                rest = &variable[end + 1..];
+            }
+            None => {
+                expanded.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+
+    expanded.push_str(rest);
+    expanded
+}
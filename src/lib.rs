@@ -1,10 +1,34 @@
 pub mod auth;
+pub mod blocked_servers;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "testing")]
+pub mod cassette;
+pub mod certificates;
+pub mod chat;
+pub mod client;
 pub mod consts;
 pub mod error;
+pub mod http;
 pub mod invalidate;
+pub mod launch;
+#[cfg(feature = "server")]
+pub mod password;
+pub mod pool;
+pub mod profile_key;
+pub mod rate_limit;
+pub mod realms;
 pub mod refresh;
+pub mod report;
+pub mod services;
 pub mod session;
+pub mod signing;
 pub mod signout;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "server")]
+pub mod throttle;
+pub mod transport;
 pub mod types;
 pub mod validate;
 
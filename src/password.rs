@@ -0,0 +1,215 @@
+//! Password hashing for self-hosted authentication servers
+//!
+//! Requires the `server` feature.
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier as _, SaltString};
+use argon2::Argon2;
+use md5::Md5;
+use sha2::{Digest, Sha256, Sha512};
+use uuid::Uuid;
+
+use crate::{Error, Result};
+
+/// A strategy to hash passwords and check them against stored hashes.
+pub trait PasswordVerifier: Send + Sync {
+    /// Check `password` against a stored `hash`.
+    ///
+    /// Malformed hashes are treated as a mismatch.
+    fn verify(&self, password: &str, hash: &str) -> bool;
+
+    /// Hash `password` for storage.
+    fn hash(&self, password: &str) -> Result<String>;
+
+    /// Whether a stored `hash` should be replaced by a fresh [`hash`](PasswordVerifier::hash)
+    /// the next time the user logs in successfully, default is `false`.
+    fn needs_rehash(&self, _hash: &str) -> bool {
+        false
+    }
+}
+
+/// Argon2id in the PHC string format (`$argon2id$v=19$...`).
+///
+/// Hashes made with the other Argon2 variants, like the `$argon2i$` hashes
+/// produced by PHP, are verified as well.
+#[derive(Default)]
+pub struct Argon2Verifier {
+    argon2: Argon2<'static>,
+}
+
+/// bcrypt in the modular crypt format (`$2b$...`, `$2y$...`).
+pub struct BcryptVerifier {
+    cost: u32,
+}
+
+/// Password hashing methods supported by Blessing Skin, named after its
+/// `PWD_METHOD` setting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlessingSkinCipher {
+    Md5,
+    Salted2Md5,
+    Sha256,
+    Salted2Sha256,
+    Salted2Sha512,
+    Bcrypt,
+    Argon2i,
+    PhpPasswordHash,
+}
+
+/// Verify passwords imported from a Blessing Skin `users` table.
+///
+/// Hashes in the PHP `password_hash` format (bcrypt and Argon2) are detected
+/// by their prefix, everything else is checked with the configured legacy
+/// `cipher` and `salt` (the `SALT` from Blessing Skin's `.env`). New hashes
+/// are always Argon2id, and [`needs_rehash`](PasswordVerifier::needs_rehash)
+/// reports every legacy hash so accounts migrate as their owners log in.
+pub struct BlessingSkinVerifier {
+    cipher: BlessingSkinCipher,
+    salt: String,
+    argon2: Argon2Verifier,
+}
+
+impl Argon2Verifier {
+    pub fn new() -> Argon2Verifier {
+        Argon2Verifier::default()
+    }
+}
+
+impl PasswordVerifier for Argon2Verifier {
+    fn verify(&self, password: &str, hash: &str) -> bool {
+        match PasswordHash::new(hash) {
+            Ok(hash) => self
+                .argon2
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    fn hash(&self, password: &str) -> Result<String> {
+        let salt = SaltString::encode_b64(Uuid::new_v4().as_bytes())
+            .map_err(|error| Error::PasswordHash(error.to_string()))?;
+        self.argon2
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|error| Error::PasswordHash(error.to_string()))
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        !hash.starts_with("$argon2id$")
+    }
+}
+
+impl Default for BcryptVerifier {
+    fn default() -> BcryptVerifier {
+        BcryptVerifier {
+            cost: bcrypt::DEFAULT_COST,
+        }
+    }
+}
+
+impl BcryptVerifier {
+    pub fn new() -> BcryptVerifier {
+        BcryptVerifier::default()
+    }
+
+    /// Set the cost of new hashes, default is `12`.
+    pub fn cost(&mut self, cost: u32) -> &mut BcryptVerifier {
+        self.cost = cost;
+        self
+    }
+}
+
+impl PasswordVerifier for BcryptVerifier {
+    fn verify(&self, password: &str, hash: &str) -> bool {
+        bcrypt::verify(password, hash).unwrap_or(false)
+    }
+
+    fn hash(&self, password: &str) -> Result<String> {
+        bcrypt::hash(password, self.cost).map_err(|error| Error::PasswordHash(error.to_string()))
+    }
+}
+
+impl BlessingSkinCipher {
+    /// Parse a `PWD_METHOD` value, such as `SALTED2SHA256`.
+    pub fn from_name(name: &str) -> Option<BlessingSkinCipher> {
+        match name.to_ascii_uppercase().as_str() {
+            "MD5" => Some(BlessingSkinCipher::Md5),
+            "SALTED2MD5" => Some(BlessingSkinCipher::Salted2Md5),
+            "SHA256" => Some(BlessingSkinCipher::Sha256),
+            "SALTED2SHA256" => Some(BlessingSkinCipher::Salted2Sha256),
+            "SALTED2SHA512" => Some(BlessingSkinCipher::Salted2Sha512),
+            "BCRYPT" => Some(BlessingSkinCipher::Bcrypt),
+            "ARGON2I" => Some(BlessingSkinCipher::Argon2i),
+            "PHP_PASSWORD_HASH" => Some(BlessingSkinCipher::PhpPasswordHash),
+            _ => None,
+        }
+    }
+
+    /// Compute the legacy digest, `None` for the `password_hash` based methods.
+    fn digest(self, password: &str, salt: &str) -> Option<String> {
+        let digest = match self {
+            BlessingSkinCipher::Md5 => hex::<Md5>(format!("{}{}", password, salt).as_bytes()),
+            BlessingSkinCipher::Salted2Md5 => {
+                hex::<Md5>(format!("{}{}", hex::<Md5>(password.as_bytes()), salt).as_bytes())
+            }
+            BlessingSkinCipher::Sha256 => hex::<Sha256>(format!("{}{}", password, salt).as_bytes()),
+            BlessingSkinCipher::Salted2Sha256 => {
+                hex::<Sha256>(format!("{}{}", hex::<Sha256>(password.as_bytes()), salt).as_bytes())
+            }
+            BlessingSkinCipher::Salted2Sha512 => {
+                hex::<Sha512>(format!("{}{}", hex::<Sha512>(password.as_bytes()), salt).as_bytes())
+            }
+            BlessingSkinCipher::Bcrypt
+            | BlessingSkinCipher::Argon2i
+            | BlessingSkinCipher::PhpPasswordHash => return None,
+        };
+        Some(digest)
+    }
+}
+
+impl BlessingSkinVerifier {
+    /// Create a verifier from Blessing Skin's `PWD_METHOD` and `SALT`.
+    pub fn new<S: Into<String>>(cipher: BlessingSkinCipher, salt: S) -> BlessingSkinVerifier {
+        BlessingSkinVerifier {
+            cipher,
+            salt: salt.into(),
+            argon2: Argon2Verifier::new(),
+        }
+    }
+}
+
+impl PasswordVerifier for BlessingSkinVerifier {
+    fn verify(&self, password: &str, hash: &str) -> bool {
+        if hash.starts_with("$argon2") {
+            return self.argon2.verify(password, hash);
+        }
+        if hash.starts_with("$2") {
+            return bcrypt::verify(password, hash).unwrap_or(false);
+        }
+        match self.cipher.digest(password, &self.salt) {
+            Some(digest) => {
+                constant_time_eq(digest.as_bytes(), hash.to_ascii_lowercase().as_bytes())
+            }
+            None => false,
+        }
+    }
+
+    fn hash(&self, password: &str) -> Result<String> {
+        self.argon2.hash(password)
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        self.argon2.needs_rehash(hash)
+    }
+}
+
+fn hex<D: Digest>(data: &[u8]) -> String {
+    D::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,175 @@
+//! Failover between mirrored servers
+//!
+//! A [ServerPool] is an ordered list of base urls serving the same API.
+//! Requests go to the first healthy server, and move on to the next one
+//! when a server cannot be reached or answers with `429` or `503`. Requests
+//! which are idempotent also move on when a server times out or answers
+//! with another `5xx` status, since the server may have processed the
+//! others. A failing server is skipped for a cooldown, unless every server
+//! is unhealthy.
+
+use reqwest::{IntoUrl, StatusCode, Url};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::client::Client;
+use crate::http::{HttpRequest, HttpResponse};
+use crate::{Error, Result};
+
+/// `ServerPool` picks a healthy server of several mirrors
+///
+/// Clones share the health of the servers.
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::pool::ServerPool;
+/// # use sage_auth::session::Session;
+/// # use sage_auth::client::Client;
+/// # use sage_auth::error::Result;
+/// # async fn anonymous() -> Result<()> {
+/// let pool = ServerPool::new(vec![
+///     "https://auth1.example.com",
+///     "https://auth2.example.com",
+/// ])?;
+///
+/// let session = Session::login_with_pool(&Client::new(), &pool, "USERNAME", "PASSWORD").await?;
+/// println!("logged in at {}", session.auth_server());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ServerPool {
+    servers: Vec<Url>,
+    cooldown: Duration,
+    unhealthy_until: Arc<Mutex<HashMap<Url, Instant>>>,
+}
+
+impl ServerPool {
+    /// Create a pool of `servers`, in order of preference.
+    pub fn new<I, T>(servers: I) -> Result<ServerPool>
+    where
+        I: IntoIterator<Item = T>,
+        T: IntoUrl,
+    {
+        let servers = servers
+            .into_iter()
+            .map(|server| server.into_url())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if servers.is_empty() {
+            return Err(Error::MissingField("servers"));
+        }
+
+        Ok(ServerPool {
+            servers,
+            cooldown: Duration::from_secs(30),
+            unhealthy_until: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Create a pool of a single server.
+    pub fn single(server: Url) -> ServerPool {
+        ServerPool {
+            servers: vec![server],
+            cooldown: Duration::from_secs(30),
+            unhealthy_until: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Set how long an unhealthy server is skipped, default is 30 seconds.
+    pub fn cooldown(&mut self, cooldown: Duration) -> &mut ServerPool {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Get the servers of the pool, in order of preference.
+    pub fn servers(&self) -> &[Url] {
+        &self.servers
+    }
+
+    /// Whether `server` is not cooling down.
+    pub fn is_healthy(&self, server: &Url) -> bool {
+        match self.unhealthy_until.lock().unwrap().get(server) {
+            Some(until) => *until <= Instant::now(),
+            None => true,
+        }
+    }
+
+    /// Skip `server` for the cooldown.
+    pub fn mark_unhealthy(&self, server: &Url) {
+        self.unhealthy_until
+            .lock()
+            .unwrap()
+            .insert(server.clone(), Instant::now() + self.cooldown);
+    }
+
+    /// Stop skipping `server`.
+    pub fn mark_healthy(&self, server: &Url) {
+        self.unhealthy_until.lock().unwrap().remove(server);
+    }
+
+    /// Get the servers in the order they are tried: the healthy ones in
+    /// order of preference, then the unhealthy ones by when they recover.
+    pub fn candidates(&self) -> Vec<Url> {
+        let now = Instant::now();
+        let unhealthy_until = self.unhealthy_until.lock().unwrap();
+
+        let mut healthy = vec![];
+        let mut unhealthy = vec![];
+        for server in &self.servers {
+            match unhealthy_until.get(server) {
+                Some(until) if *until > now => unhealthy.push((*until, server.clone())),
+                _ => healthy.push(server.clone()),
+            }
+        }
+        unhealthy.sort_by_key(|(until, _)| *until);
+
+        healthy.extend(unhealthy.into_iter().map(|(_, server)| server));
+        healthy
+    }
+
+    /// Send the request which `build` makes for each server in turn, until
+    /// one answers with a response which is not failed over, see the
+    /// [module documentation](self). Returns the server which answered and
+    /// its response.
+    pub(crate) async fn send<F>(
+        &self,
+        client: &Client,
+        idempotent: bool,
+        build: F,
+    ) -> Result<(Url, HttpResponse)>
+    where
+        F: Fn(&Url) -> Result<HttpRequest>,
+    {
+        let candidates = self.candidates();
+        let last = candidates.len() - 1;
+
+        for (i, server) in candidates.into_iter().enumerate() {
+            let result = client.send_http(build(&server)?, idempotent).await;
+
+            let fail_over = match &result {
+                Ok(response) => {
+                    let status = response.status;
+                    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+                        self.mark_unhealthy(&server);
+                    } else {
+                        self.mark_healthy(&server);
+                    }
+                    status == StatusCode::TOO_MANY_REQUESTS
+                        || status == StatusCode::SERVICE_UNAVAILABLE
+                        || (idempotent && status.is_server_error())
+                }
+                Err(error) => {
+                    if error.is_connect() || error.is_timeout() {
+                        self.mark_unhealthy(&server);
+                    }
+                    error.is_connect() || (idempotent && error.is_timeout())
+                }
+            };
+            if !fail_over || i == last {
+                return result.map(|response| (server, response));
+            }
+        }
+        unreachable!("a server pool is never empty")
+    }
+}
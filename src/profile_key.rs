@@ -0,0 +1,287 @@
+//! Profile public keys
+//!
+//! Since 1.19, players sign chat with a key pair issued by the services API.
+//! The public half is sent to servers together with Mojang's signature of it,
+//! in the login packet before 1.19.3 and in the chat session packet since.
+//! Servers check that signature with [ProfileKeyVerifier].
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use reqwest::{IntoUrl, Method, StatusCode, Url};
+use serde_derive::Deserialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration as StdDuration, Instant};
+use uuid::Uuid;
+
+use crate::client::{Client, DEFAULT_CLIENT};
+use crate::consts::DEFAULT_SERVICES_SERVER;
+use crate::error::ProfileKeyError;
+use crate::http::HttpRequest;
+use crate::signing::PublicKey;
+use crate::{Error, Result};
+
+/// A profile public key, in the form sent in packets
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfilePublicKey {
+    /// Expiry in milliseconds since the Unix epoch.
+    pub expires_at: i64,
+
+    /// X.509 `SubjectPublicKeyInfo` DER of the key.
+    pub key: Vec<u8>,
+
+    /// Mojang's signature of the key.
+    pub signature: Vec<u8>,
+}
+
+/// Keys which Mojang signs with, published at `/publickeys`
+#[derive(Clone, Debug, Default)]
+pub struct ServicesKeys {
+    /// Keys which sign profile properties, such as `textures`.
+    pub profile_property_keys: Vec<PublicKey>,
+
+    /// Keys which sign profile public keys.
+    pub player_certificate_keys: Vec<PublicKey>,
+}
+
+/// `ProfileKeyVerifier` checks that a player's public key was signed by Mojang
+///
+/// Mojang's keys are fetched on first use and cached.
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::profile_key::{ProfilePublicKey, ProfileKeyVerifier};
+/// # use sage_auth::error::Result;
+/// # use uuid::Uuid;
+/// # async fn anonymous(profile_id: Uuid, key: ProfilePublicKey) -> Result<()> {
+/// let verifier = ProfileKeyVerifier::new();
+/// let public_key = verifier.verify(profile_id, &key).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ProfileKeyVerifier {
+    server: Url,
+    endpoint: String,
+    cache_ttl: StdDuration,
+    clock_skew: Duration,
+    cache: Mutex<Option<(Instant, Arc<ServicesKeys>)>>,
+    client: Client,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServicesKeysResponse {
+    profile_property_keys: Vec<KeyInfo>,
+    player_certificate_keys: Vec<KeyInfo>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyInfo {
+    public_key: String,
+}
+
+impl ProfilePublicKey {
+    /// Get the expiry as a [DateTime].
+    pub fn expires_at(&self) -> Result<DateTime<Utc>> {
+        Utc.timestamp_millis_opt(self.expires_at)
+            .single()
+            .ok_or_else(|| Error::Key(format!("invalid expiry: {}", self.expires_at)))
+    }
+
+    /// Parse the key.
+    pub fn public_key(&self) -> Result<PublicKey> {
+        PublicKey::from_der(&self.key)
+    }
+
+    /// Whether the key has expired, allowing for `clock_skew`.
+    pub fn has_expired(&self, clock_skew: Duration) -> bool {
+        match self.expires_at() {
+            Ok(expires_at) => expires_at + clock_skew < Utc::now(),
+            Err(_) => true,
+        }
+    }
+
+    /// Get the data which Mojang signs, since 1.19.1: the profile id,
+    /// expiry and key DER.
+    pub fn signed_payload(&self, profile_id: Uuid) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(24 + self.key.len());
+        payload.extend_from_slice(profile_id.as_bytes());
+        payload.extend_from_slice(&self.expires_at.to_be_bytes());
+        payload.extend_from_slice(&self.key);
+        payload
+    }
+
+    /// Get the data which Mojang signs in 1.19.0: the expiry followed by
+    /// the key as a PEM.
+    pub fn legacy_signed_payload(&self) -> Vec<u8> {
+        let encoded = BASE64.encode(&self.key);
+        let lines: Vec<_> = encoded
+            .as_bytes()
+            .chunks(76)
+            .map(|line| std::str::from_utf8(line).unwrap())
+            .collect();
+
+        format!(
+            "{}-----BEGIN RSA PUBLIC KEY-----\n{}\n-----END RSA PUBLIC KEY-----\n",
+            self.expires_at,
+            lines.join("\n")
+        )
+        .into_bytes()
+    }
+}
+
+impl ServicesKeys {
+    /// Fetch the keys from `server`, such as `https://api.minecraftservices.com/publickeys`.
+    pub async fn fetch<T: IntoUrl>(server: T) -> Result<ServicesKeys> {
+        ServicesKeys::fetch_with_client(&DEFAULT_CLIENT, server).await
+    }
+
+    /// Fetch the keys from `server` with `client`.
+    pub async fn fetch_with_client<T: IntoUrl>(client: &Client, server: T) -> Result<ServicesKeys> {
+        let response = client
+            .send(HttpRequest::new(Method::GET, server.into_url()?))
+            .await?;
+
+        match response.status {
+            StatusCode::OK => {
+                let response = response.json::<ServicesKeysResponse>()?;
+                Ok(ServicesKeys {
+                    profile_property_keys: parse_keys(&response.profile_property_keys)?,
+                    player_certificate_keys: parse_keys(&response.player_certificate_keys)?,
+                })
+            }
+            _ => Err(response.error()),
+        }
+    }
+
+    /// Verify a key sent by the player with `profile_id`, as sent since 1.19.1.
+    ///
+    /// Returns the parsed key if it was signed by Mojang and has not expired,
+    /// allowing for `clock_skew`.
+    pub fn verify(
+        &self,
+        profile_id: Uuid,
+        key: &ProfilePublicKey,
+        clock_skew: Duration,
+    ) -> Result<PublicKey> {
+        self.check(&key.signed_payload(profile_id), key, clock_skew)
+    }
+
+    /// Verify a key in the form of 1.19.0.
+    pub fn verify_legacy(&self, key: &ProfilePublicKey, clock_skew: Duration) -> Result<PublicKey> {
+        self.check(&key.legacy_signed_payload(), key, clock_skew)
+    }
+
+    fn check(
+        &self,
+        payload: &[u8],
+        key: &ProfilePublicKey,
+        clock_skew: Duration,
+    ) -> Result<PublicKey> {
+        let public_key = key
+            .public_key()
+            .map_err(|_| Error::ProfileKey(ProfileKeyError::InvalidKey))?;
+
+        let signed = self
+            .player_certificate_keys
+            .iter()
+            .any(|mojang_key| mojang_key.verify_bytes(payload, &key.signature).is_ok());
+        if !signed {
+            return Err(Error::ProfileKey(ProfileKeyError::InvalidSignature));
+        }
+        if key.has_expired(clock_skew) {
+            return Err(Error::ProfileKey(ProfileKeyError::Expired));
+        }
+
+        Ok(public_key)
+    }
+}
+
+impl Default for ProfileKeyVerifier {
+    fn default() -> ProfileKeyVerifier {
+        ProfileKeyVerifier {
+            server: (*DEFAULT_SERVICES_SERVER).clone(),
+            endpoint: "/publickeys".to_owned(),
+            cache_ttl: StdDuration::from_secs(24 * 60 * 60),
+            clock_skew: Duration::zero(),
+            cache: Mutex::new(None),
+            client: DEFAULT_CLIENT.clone(),
+        }
+    }
+}
+
+impl ProfileKeyVerifier {
+    pub fn new() -> ProfileKeyVerifier {
+        ProfileKeyVerifier::default()
+    }
+
+    /// Set base url, default is `https://api.minecraftservices.com`.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut ProfileKeyVerifier> {
+        self.server = server.into_url()?;
+        Ok(self)
+    }
+
+    /// set endpoint, default is `/publickeys`.
+    pub fn endpoint<S: Into<String>>(&mut self, endpoint: S) -> &mut ProfileKeyVerifier {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Set how long fetched keys are cached, default is one day.
+    pub fn cache_ttl(&mut self, cache_ttl: StdDuration) -> &mut ProfileKeyVerifier {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    /// Set how long keys are accepted after they expire, default is zero.
+    pub fn clock_skew(&mut self, clock_skew: Duration) -> &mut ProfileKeyVerifier {
+        self.clock_skew = clock_skew;
+        self
+    }
+
+    /// Set client which sends the requests, default is [`Client::default`].
+    pub fn client(&mut self, client: &Client) -> &mut ProfileKeyVerifier {
+        self.client = client.clone();
+        self
+    }
+
+    /// Get Mojang's keys, fetching them if the cache is empty or outdated.
+    pub async fn keys(&self) -> Result<Arc<ServicesKeys>> {
+        if let Some((fetched_at, keys)) = &*self.cache.lock().unwrap() {
+            if fetched_at.elapsed() < self.cache_ttl {
+                return Ok(keys.clone());
+            }
+        }
+
+        let keys = Arc::new(
+            ServicesKeys::fetch_with_client(&self.client, self.server.join(&self.endpoint)?)
+                .await?,
+        );
+        *self.cache.lock().unwrap() = Some((Instant::now(), keys.clone()));
+        Ok(keys)
+    }
+
+    /// Verify a key sent by the player with `profile_id`, as sent since 1.19.1.
+    ///
+    /// Returns [`Error::ProfileKey`] if the key is rejected.
+    pub async fn verify(&self, profile_id: Uuid, key: &ProfilePublicKey) -> Result<PublicKey> {
+        self.keys().await?.verify(profile_id, key, self.clock_skew)
+    }
+
+    /// Verify a key in the form of 1.19.0.
+    pub async fn verify_legacy(&self, key: &ProfilePublicKey) -> Result<PublicKey> {
+        self.keys().await?.verify_legacy(key, self.clock_skew)
+    }
+}
+
+fn parse_keys(keys: &[KeyInfo]) -> Result<Vec<PublicKey>> {
+    keys.iter()
+        .map(|key| {
+            let der = BASE64
+                .decode(&key.public_key)
+                .map_err(|error| Error::Key(error.to_string()))?;
+            PublicKey::from_der(&der)
+        })
+        .collect()
+}
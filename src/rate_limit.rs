@@ -0,0 +1,202 @@
+//! Client-side rate limiting
+//!
+//! Mojang limits how often an IP can call each group of endpoints, such as
+//! 600 profile lookups per 10 minutes, and answers `429` beyond that.
+//! A [RateLimiter] keeps a token bucket per server and [EndpointGroup], and
+//! delays requests until their bucket has a token instead of exceeding it.
+
+use reqwest::Url;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Endpoints which share a quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointGroup {
+    /// `/authenticate`, `/refresh`, `/validate`, `/invalidate` and `/signout`.
+    Auth,
+
+    /// Profile lookups by name or id, on the session server and the APIs.
+    Profile,
+
+    /// `/session/minecraft/join` and `/session/minecraft/hasJoined`.
+    Session,
+
+    /// Everything else, such as the services API.
+    Other,
+}
+
+/// A number of requests per period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub requests: u32,
+    pub period: Duration,
+}
+
+/// `RateLimiter` queues requests to stay within the quota of each group
+///
+/// Quotas apply to each server separately. The defaults follow Mojang's
+/// limits, third-party Yggdrasil servers may need others.
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::client::Client;
+/// # use sage_auth::rate_limit::{EndpointGroup, Quota, RateLimiter};
+/// # use std::time::Duration;
+/// let mut limiter = RateLimiter::new();
+/// limiter.quota(
+///     EndpointGroup::Auth,
+///     Some(Quota {
+///         requests: 10,
+///         period: Duration::from_secs(60),
+///     }),
+/// );
+///
+/// let mut client = Client::new();
+/// client.rate_limiter(limiter);
+/// ```
+#[derive(Debug)]
+pub struct RateLimiter {
+    quotas: HashMap<EndpointGroup, Quota>,
+    buckets: Mutex<HashMap<(String, EndpointGroup), Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl EndpointGroup {
+    /// Get the group of the endpoint at `url`.
+    pub fn of(url: &Url) -> EndpointGroup {
+        let path = url.path().trim_end_matches('/');
+        let last = path.rsplit('/').next().unwrap_or("");
+
+        if [
+            "authenticate",
+            "refresh",
+            "validate",
+            "invalidate",
+            "signout",
+        ]
+        .contains(&last)
+        {
+            EndpointGroup::Auth
+        } else if path.ends_with("/session/minecraft/join")
+            || path.ends_with("/session/minecraft/hasJoined")
+        {
+            EndpointGroup::Session
+        } else if path.contains("/session/minecraft/profile/")
+            || path.contains("/users/profiles/")
+            || path.contains("/profiles/minecraft")
+            || path.contains("/minecraft/profile/lookup/")
+        {
+            EndpointGroup::Profile
+        } else {
+            EndpointGroup::Other
+        }
+    }
+}
+
+impl Quota {
+    fn per_second(&self) -> f64 {
+        f64::from(self.requests) / self.period.as_secs_f64()
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> RateLimiter {
+        let mut limiter = RateLimiter::unlimited();
+        limiter
+            .quota(
+                EndpointGroup::Profile,
+                Some(Quota {
+                    requests: 600,
+                    period: Duration::from_secs(600),
+                }),
+            )
+            .quota(
+                EndpointGroup::Auth,
+                Some(Quota {
+                    requests: 20,
+                    period: Duration::from_secs(60),
+                }),
+            );
+        limiter
+    }
+}
+
+impl RateLimiter {
+    pub fn new() -> RateLimiter {
+        RateLimiter::default()
+    }
+
+    /// Create a limiter without any quota.
+    pub fn unlimited() -> RateLimiter {
+        RateLimiter {
+            quotas: HashMap::new(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set quota of `group`, `None` for no limit. Defaults are 600 requests
+    /// per 10 minutes for [EndpointGroup::Profile], 20 per minute for
+    /// [EndpointGroup::Auth], and no limit for the others.
+    pub fn quota(&mut self, group: EndpointGroup, quota: Option<Quota>) -> &mut RateLimiter {
+        match quota {
+            Some(quota) if quota.requests > 0 && !quota.period.is_zero() => {
+                self.quotas.insert(group, quota);
+            }
+            _ => {
+                self.quotas.remove(&group);
+            }
+        }
+        self.buckets.get_mut().unwrap().clear();
+        self
+    }
+
+    /// Take a token for a request to `url`, and get how long to wait before
+    /// sending it. The token is taken even if there is a wait, so that
+    /// requests are sent in the order they asked.
+    pub fn reserve(&self, url: &Url) -> Duration {
+        let group = EndpointGroup::of(url);
+        let quota = match self.quotas.get(&group) {
+            Some(quota) => *quota,
+            None => return Duration::from_secs(0),
+        };
+
+        let server = format!(
+            "{}:{}",
+            url.host_str().unwrap_or(""),
+            url.port_or_known_default().unwrap_or(0)
+        );
+        let key = (server, group);
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: f64::from(quota.requests),
+            updated_at: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens =
+            (bucket.tokens + elapsed * quota.per_second()).min(f64::from(quota.requests));
+        bucket.updated_at = now;
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / quota.per_second())
+        }
+    }
+
+    /// Wait until a request to `url` can be sent.
+    pub async fn acquire(&self, url: &Url) {
+        let delay = self.reserve(url);
+        if delay > Duration::from_secs(0) {
+            tokio::time::delay_for(delay).await;
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Realms API
+//!
+//! Realms authorizes requests with a cookie built from the session:
+//! `sid=token:<access_token>:<profile id>;user=<profile name>;version=<game version>`.
+
+use reqwest::header::{COOKIE, RETRY_AFTER};
+use reqwest::{IntoUrl, Method, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde_derive::Deserialize;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::client::{Client, DEFAULT_CLIENT};
+use crate::consts::DEFAULT_REALMS_SERVER;
+use crate::http::{HttpRequest, HttpResponse};
+use crate::Result;
+
+/// `RealmsClient` is used to make requests to the Realms API
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::session::Session;
+/// # use sage_auth::error::Result;
+/// # async fn anonymous() -> Result<()> {
+/// let session = Session::login("USERNAME", "PASSWORD").await?;
+/// let realms = session.realms("1.20.4")?;
+///
+/// for world in realms.worlds().await? {
+///     let address = realms.join_address(world.id).await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct RealmsClient {
+    cookie: String,
+    server: Url,
+    client: Client,
+    join_attempts: u32,
+}
+
+/// A realm
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RealmsWorld {
+    pub id: i64,
+    pub owner: Option<String>,
+    #[serde(rename = "ownerUUID")]
+    pub owner_uuid: Option<Uuid>,
+    pub name: Option<String>,
+    pub motd: Option<String>,
+
+    /// `OPEN`, `CLOSED` or `UNINITIALIZED`.
+    pub state: String,
+    pub days_left: Option<i32>,
+    #[serde(default)]
+    pub expired: bool,
+    #[serde(default)]
+    pub expired_trial: bool,
+
+    /// `NORMAL`, `MINIGAME`, `ADVENTUREMAP`, `EXPERIENCE` or `INSPIRATION`.
+    pub world_type: Option<String>,
+    pub max_players: Option<i32>,
+    pub active_slot: Option<i32>,
+    #[serde(default)]
+    pub member: bool,
+}
+
+/// Where to connect to a realm, see [`RealmsClient::join_address`]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RealmsAddress {
+    /// `host:port` of the server.
+    pub address: String,
+    pub resource_pack_url: Option<String>,
+    pub resource_pack_hash: Option<String>,
+    #[serde(default)]
+    pub pending_update: bool,
+}
+
+/// Whether the client version can play on Realms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    Compatible,
+
+    /// The client is older than the Realms servers.
+    Outdated,
+
+    /// The client is newer than the Realms servers, such as a snapshot.
+    Other,
+}
+
+/// An invitation to a realm
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingInvite {
+    pub invitation_id: String,
+    pub world_name: String,
+    pub world_owner_name: String,
+    #[serde(rename = "worldOwnerUuid")]
+    pub world_owner_uuid: Uuid,
+
+    /// Milliseconds since the Unix epoch.
+    pub date: i64,
+}
+
+#[derive(Deserialize)]
+struct WorldsResponse {
+    servers: Vec<RealmsWorld>,
+}
+
+#[derive(Deserialize)]
+struct InvitesResponse {
+    invites: Vec<PendingInvite>,
+}
+
+impl RealmsClient {
+    /// Create a client for the player with `profile_id` and `profile_name`,
+    /// playing `version` of the game, such as `1.20.4`.
+    pub fn new(
+        access_token: &str,
+        profile_id: Uuid,
+        profile_name: &str,
+        version: &str,
+    ) -> RealmsClient {
+        RealmsClient {
+            cookie: format!(
+                "sid=token:{}:{};user={};version={}",
+                access_token,
+                profile_id.to_simple(),
+                profile_name,
+                version
+            ),
+            server: (*DEFAULT_REALMS_SERVER).clone(),
+            client: DEFAULT_CLIENT.clone(),
+            join_attempts: 20,
+        }
+    }
+
+    /// Set base url, default is `https://pc.realms.minecraft.net`.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut RealmsClient> {
+        self.server = server.into_url()?;
+        Ok(self)
+    }
+
+    /// Set client which sends the requests, default is [`Client::default`].
+    pub fn client(&mut self, client: &Client) -> &mut RealmsClient {
+        self.client = client.clone();
+        self
+    }
+
+    /// Set how many times [join_address](RealmsClient::join_address) asks
+    /// while the realm is starting, default is `20`.
+    pub fn join_attempts(&mut self, join_attempts: u32) -> &mut RealmsClient {
+        self.join_attempts = join_attempts;
+        self
+    }
+
+    /// List the realms which the player owns or is a member of.
+    pub async fn worlds(&self) -> Result<Vec<RealmsWorld>> {
+        let response: WorldsResponse = self.get_json("/worlds").await?;
+        Ok(response.servers)
+    }
+
+    /// Get the address of the realm with `world_id`, starting it if needed.
+    ///
+    /// While the realm is starting, Realms answers `503 Service Unavailable`
+    /// with a `Retry-After` header. The request is repeated after the given
+    /// delay, up to [join_attempts](RealmsClient::join_attempts) times;
+    /// the retry policy of the client does not repeat it further.
+    pub async fn join_address(&self, world_id: i64) -> Result<RealmsAddress> {
+        let endpoint = format!("/worlds/v1/{}/join/pc", world_id);
+        let client = self.client.without_unavailable_retries();
+        let mut attempt = 1;
+
+        loop {
+            let response = client.send(self.request(Method::GET, &endpoint)?).await?;
+            if response.status != StatusCode::SERVICE_UNAVAILABLE || attempt >= self.join_attempts {
+                return json_response(response);
+            }
+
+            let delay = response
+                .headers
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(5);
+            tokio::time::delay_for(Duration::from_secs(delay)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Whether Realms is available to the player, such as in their country.
+    pub async fn is_available(&self) -> Result<bool> {
+        let response = self
+            .client
+            .send(self.request(Method::GET, "/mco/available")?)
+            .await?;
+        Ok(text_response(response)?.trim() == "true")
+    }
+
+    /// Check whether the client version can play on Realms.
+    pub async fn compatibility(&self) -> Result<Compatibility> {
+        let response = self
+            .client
+            .send(self.request(Method::GET, "/mco/client/compatible")?)
+            .await?;
+
+        match text_response(response)?.trim() {
+            "COMPATIBLE" => Ok(Compatibility::Compatible),
+            "OUTDATED" => Ok(Compatibility::Outdated),
+            _ => Ok(Compatibility::Other),
+        }
+    }
+
+    /// Get the number of pending invitations.
+    pub async fn pending_invites_count(&self) -> Result<u32> {
+        self.get_json("/invites/count/pending").await
+    }
+
+    /// List the pending invitations.
+    pub async fn pending_invites(&self) -> Result<Vec<PendingInvite>> {
+        let response: InvitesResponse = self.get_json("/invites/pending").await?;
+        Ok(response.invites)
+    }
+
+    /// Accept the invitation with `invitation_id`.
+    pub async fn accept_invite(&self, invitation_id: &str) -> Result<()> {
+        let endpoint = format!("/invites/accept/{}", invitation_id);
+        let response = self
+            .client
+            .send(self.request(Method::PUT, &endpoint)?)
+            .await?;
+        text_response(response).map(|_| ())
+    }
+
+    /// Reject the invitation with `invitation_id`.
+    pub async fn reject_invite(&self, invitation_id: &str) -> Result<()> {
+        let endpoint = format!("/invites/reject/{}", invitation_id);
+        let response = self
+            .client
+            .send(self.request(Method::PUT, &endpoint)?)
+            .await?;
+        text_response(response).map(|_| ())
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
+        let response = self
+            .client
+            .send(self.request(Method::GET, endpoint)?)
+            .await?;
+        json_response(response)
+    }
+
+    fn request(&self, method: Method, endpoint: &str) -> Result<HttpRequest> {
+        let mut request = HttpRequest::new(method, self.server.join(endpoint)?);
+        request.header(COOKIE, &self.cookie)?;
+        Ok(request)
+    }
+}
+
+fn json_response<T: DeserializeOwned>(response: HttpResponse) -> Result<T> {
+    match response.status {
+        StatusCode::OK => Ok(response.json()?),
+        _ => Err(response.error()),
+    }
+}
+
+fn text_response(response: HttpResponse) -> Result<String> {
+    match response.status {
+        StatusCode::OK | StatusCode::NO_CONTENT => Ok(response.text()),
+        _ => Err(response.error()),
+    }
+}
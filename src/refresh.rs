@@ -1,10 +1,13 @@
 //! Refresh request
 
-use reqwest::{IntoUrl, StatusCode, Url};
+use reqwest::{IntoUrl, Method, StatusCode, Url};
 use serde_derive::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::client::{Client, DEFAULT_CLIENT};
 use crate::consts::DEFAULT_SERVER;
+use crate::http::{HttpRequest, HttpResponse};
+use crate::pool::ServerPool;
 use crate::types::{Profile, User};
 use crate::{Error, Result};
 
@@ -38,8 +41,10 @@ struct RefreshParams<'a> {
 /// ```
 pub struct RefreshBuilder<'a> {
     params: RefreshParams<'a>,
-    server: Url,
+    servers: ServerPool,
     endpoint: &'a str,
+    client: Client,
+    answered: Option<Url>,
 }
 
 /// Response body from Mojang server
@@ -59,6 +64,7 @@ pub struct RefreshResponse {
     pub user: Option<User>,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for RefreshParams<'_> {
     fn default() -> RefreshParams<'static> {
         RefreshParams {
@@ -73,8 +79,10 @@ impl Default for RefreshBuilder<'_> {
     fn default() -> RefreshBuilder<'static> {
         RefreshBuilder {
             params: RefreshParams::default(),
-            server: (*DEFAULT_SERVER).clone(),
+            servers: ServerPool::single((*DEFAULT_SERVER).clone()),
             endpoint: "/refresh",
+            client: DEFAULT_CLIENT.clone(),
+            answered: None,
         }
     }
 }
@@ -105,18 +113,56 @@ impl<'a> RefreshBuilder<'a> {
 
     /// Set base url, default is `https://authserver.mojang.com`.
     pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut RefreshBuilder<'a>> {
-        self.server = server.into_url()?;
+        self.servers = ServerPool::single(server.into_url()?);
         Ok(self)
     }
 
+    /// Set mirrored servers to fail over between, instead of a single
+    /// [server](Self::server).
+    pub fn servers(&mut self, servers: &ServerPool) -> &mut RefreshBuilder<'a> {
+        self.servers = servers.clone();
+        self
+    }
+
     /// set endpoint, default is `/authenticate`.
     pub fn endpoint(&mut self, endpoint: &'a str) -> &mut RefreshBuilder<'a> {
         self.endpoint = endpoint;
         self
     }
 
+    /// Set client which sends the request, default is [`Client::default`].
+    pub fn client(&mut self, client: &Client) -> &mut RefreshBuilder<'a> {
+        self.client = client.clone();
+        self
+    }
+
+    /// Get the server which answered the last request, see
+    /// [servers](Self::servers).
+    pub fn answered_server(&self) -> Option<&Url> {
+        self.answered.as_ref()
+    }
+
+    /// Build the request without sending it, for the first server to try.
+    /// See [http](crate::http).
+    pub fn http_request(&mut self) -> Result<HttpRequest> {
+        self.check()?;
+        self.http_request_to(&self.servers.candidates()[0])
+    }
+
     /// Make a request with the given parameters.
     pub async fn request(&mut self) -> Result<RefreshResponse> {
+        self.check()?;
+
+        let (server, response) = self
+            .servers
+            .send(&self.client, false, |server| self.http_request_to(server))
+            .await?;
+        self.answered = Some(server);
+
+        parse_response(&response)
+    }
+
+    fn check(&mut self) -> Result<()> {
         if self.params.access_token.is_none() {
             return Err(Error::MissingField("access_token"));
         }
@@ -124,16 +170,18 @@ impl<'a> RefreshBuilder<'a> {
             return Err(Error::MissingField("client_token"));
         }
 
-        let client = reqwest::Client::new();
-        let response = client
-            .post(self.server.join(self.endpoint)?)
-            .json(&self.params)
-            .send()
-            .await?;
+        Ok(())
+    }
 
-        match response.status() {
-            StatusCode::OK => Ok(response.json().await?),
-            _ => Err(Error::from_response(response).await),
-        }
+    fn http_request_to(&self, server: &Url) -> Result<HttpRequest> {
+        HttpRequest::json(Method::POST, server.join(self.endpoint)?, &self.params)
+    }
+}
+
+/// Read the response of a refresh request, see [http](crate::http).
+pub fn parse_response(response: &HttpResponse) -> Result<RefreshResponse> {
+    match response.status {
+        StatusCode::OK => response.json(),
+        _ => Err(response.error()),
     }
 }
@@ -0,0 +1,400 @@
+//! Player abuse reports
+//!
+//! Chat, skin and name reports are submitted to `/player/report` of the
+//! services API with [`ServicesClient::report`](crate::services::ServicesClient::report).
+//! Chat reports carry the reported messages and their context as evidence,
+//! with the signatures so that Mojang can check them.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::StatusCode;
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::chat::ChatMessage;
+use crate::error::ReportError;
+use crate::http::HttpResponse;
+use crate::{Error, Result};
+
+/// Longest comment which the service accepts.
+pub const MAX_COMMENTS_LENGTH: usize = 1000;
+
+/// Most messages which can be reported at once.
+pub const MAX_REPORTED_MESSAGES: usize = 4;
+
+/// Most messages which can be sent as evidence, including the reported ones.
+pub const MAX_EVIDENCE_MESSAGES: usize = 40;
+
+/// What is reported
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReportType {
+    Chat,
+    Skin,
+    Username,
+}
+
+/// Why a player is reported
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    Generic,
+    FalseReporting,
+    HateSpeech,
+    TerrorismOrViolentExtremism,
+    ChildSexualExploitationOrAbuse,
+    ImminentHarm,
+    NonConsensualIntimateImagery,
+    HarassmentOrBullying,
+    DefamationImpersonationFalseInformation,
+    SelfHarmOrSuicide,
+    AlcoholTobaccoDrugs,
+    SexuallyInappropriate,
+}
+
+/// A report as sent to the service, see [ReportBuilder]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AbuseReport {
+    pub version: i32,
+    pub id: Uuid,
+    pub report: ReportBody,
+    pub client_info: ClientInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub third_party_server_info: Option<ThirdPartyServerInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub realm_info: Option<RealmInfo>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportBody {
+    #[serde(rename = "type")]
+    pub report_type: ReportType,
+    pub opinion_comments: String,
+    pub reason: ReportReason,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<ReportEvidence>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skin_url: Option<String>,
+    pub reported_entity: ReportedEntity,
+    pub created_time: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ReportEvidence {
+    pub messages: Vec<ReportedMessage>,
+}
+
+/// A chat message sent as evidence
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportedMessage {
+    pub index: i32,
+    pub profile_id: Uuid,
+    pub session_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub salt: i64,
+
+    /// Base64 encoded signatures of the messages the sender acknowledged.
+    pub last_seen: Vec<String>,
+    pub message: String,
+
+    /// Base64 encoded signature of the message.
+    pub signature: String,
+
+    /// Whether the message is reported, rather than context.
+    pub message_reported: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportedEntity {
+    pub profile_id: Uuid,
+}
+
+/// The client which made the report
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientInfo {
+    /// Game version, such as `1.20.4`.
+    pub client_version: String,
+
+    /// Locale, such as `en-US`.
+    pub locale: String,
+}
+
+/// The server where the reported player was met
+#[derive(Serialize, Debug, Clone)]
+pub struct ThirdPartyServerInfo {
+    pub address: String,
+}
+
+/// The realm where the reported player was met
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RealmInfo {
+    pub realm_id: String,
+    pub slot_id: i32,
+}
+
+/// `ReportBuilder` is used to build an abuse report
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::chat::ChatMessage;
+/// # use sage_auth::report::{ReportBuilder, ReportReason};
+/// # use sage_auth::session::Session;
+/// # use sage_auth::error::Result;
+/// # use uuid::Uuid;
+/// # async fn anonymous(reported: Uuid, message: ChatMessage, signature: Vec<u8>) -> Result<()> {
+/// let session = Session::login("USERNAME", "PASSWORD").await?;
+/// let report = ReportBuilder::chat(reported)
+///     .reason(ReportReason::HarassmentOrBullying)
+///     .comments("Keeps insulting other players")
+///     .message(&message, &signature, true)
+///     .client_version("1.20.4")
+///     .server_address("mc.example.com")
+///     .build()?;
+/// session.services().report(&report).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ReportBuilder {
+    report_type: ReportType,
+    reported: Uuid,
+    reason: Option<ReportReason>,
+    comments: String,
+    messages: Vec<(ChatMessage, Vec<u8>, bool)>,
+    skin_url: Option<String>,
+    client_version: Option<String>,
+    locale: String,
+    server_address: Option<String>,
+    realm: Option<RealmInfo>,
+}
+
+impl ReportedMessage {
+    /// Create the evidence of a signed chat message.
+    ///
+    /// Returns [`Error::Report`] if the timestamp of the message is out of
+    /// range.
+    pub fn new(message: &ChatMessage, signature: &[u8], reported: bool) -> Result<ReportedMessage> {
+        let timestamp = Utc
+            .timestamp_millis_opt(message.timestamp)
+            .single()
+            .ok_or_else(|| invalid(format!("invalid message timestamp {}", message.timestamp)))?;
+
+        Ok(ReportedMessage {
+            index: message.index,
+            profile_id: message.sender,
+            session_id: message.session_id,
+            timestamp,
+            salt: message.salt,
+            last_seen: message
+                .last_seen
+                .iter()
+                .map(|signature| BASE64.encode(signature))
+                .collect(),
+            message: message.message.clone(),
+            signature: BASE64.encode(signature),
+            message_reported: reported,
+        })
+    }
+}
+
+impl ReportBuilder {
+    fn new(report_type: ReportType, reported: Uuid) -> ReportBuilder {
+        ReportBuilder {
+            report_type,
+            reported,
+            reason: None,
+            comments: String::new(),
+            messages: vec![],
+            skin_url: None,
+            client_version: None,
+            locale: "en-US".to_owned(),
+            server_address: None,
+            realm: None,
+        }
+    }
+
+    /// Report chat messages of the player with profile id `reported`.
+    pub fn chat(reported: Uuid) -> ReportBuilder {
+        ReportBuilder::new(ReportType::Chat, reported)
+    }
+
+    /// Report the skin at `skin_url` of the player with profile id `reported`.
+    pub fn skin<S: Into<String>>(reported: Uuid, skin_url: S) -> ReportBuilder {
+        let mut builder = ReportBuilder::new(ReportType::Skin, reported);
+        builder.skin_url = Some(skin_url.into());
+        builder
+    }
+
+    /// Report the name of the player with profile id `reported`.
+    pub fn username(reported: Uuid) -> ReportBuilder {
+        ReportBuilder::new(ReportType::Username, reported)
+    }
+
+    /// Set reason, required
+    pub fn reason(&mut self, reason: ReportReason) -> &mut ReportBuilder {
+        self.reason = Some(reason);
+        self
+    }
+
+    /// Set comments of the reporter, up to [MAX_COMMENTS_LENGTH] characters.
+    pub fn comments<S: Into<String>>(&mut self, comments: S) -> &mut ReportBuilder {
+        self.comments = comments.into();
+        self
+    }
+
+    /// Add a chat message as evidence, `reported` if it is one of the
+    /// reported messages rather than context.
+    pub fn message(
+        &mut self,
+        message: &ChatMessage,
+        signature: &[u8],
+        reported: bool,
+    ) -> &mut ReportBuilder {
+        self.messages
+            .push((message.clone(), signature.to_vec(), reported));
+        self
+    }
+
+    /// Set game version of the reporter, required
+    pub fn client_version<S: Into<String>>(&mut self, client_version: S) -> &mut ReportBuilder {
+        self.client_version = Some(client_version.into());
+        self
+    }
+
+    /// Set locale of the reporter, default is `en-US`.
+    pub fn locale<S: Into<String>>(&mut self, locale: S) -> &mut ReportBuilder {
+        self.locale = locale.into();
+        self
+    }
+
+    /// Set address of the server where the player was met.
+    pub fn server_address<S: Into<String>>(&mut self, address: S) -> &mut ReportBuilder {
+        self.server_address = Some(address.into());
+        self
+    }
+
+    /// Set the realm and its slot where the player was met.
+    pub fn realm<S: Into<String>>(&mut self, realm_id: S, slot_id: i32) -> &mut ReportBuilder {
+        self.realm = Some(RealmInfo {
+            realm_id: realm_id.into(),
+            slot_id,
+        });
+        self
+    }
+
+    /// Check the report and build it with a new id.
+    pub fn build(&self) -> Result<AbuseReport> {
+        let reason = self.reason.ok_or(Error::MissingField("reason"))?;
+        let client_version = self
+            .client_version
+            .clone()
+            .ok_or(Error::MissingField("client_version"))?;
+
+        if self.comments.chars().count() > MAX_COMMENTS_LENGTH {
+            return Err(invalid(format!(
+                "comments are longer than {} characters",
+                MAX_COMMENTS_LENGTH
+            )));
+        }
+
+        let evidence = match self.report_type {
+            ReportType::Chat => {
+                let reported = self
+                    .messages
+                    .iter()
+                    .filter(|(_, _, reported)| *reported)
+                    .count();
+                if reported == 0 {
+                    return Err(invalid("no message is reported".to_owned()));
+                }
+                if reported > MAX_REPORTED_MESSAGES {
+                    return Err(invalid(format!(
+                        "more than {} messages are reported",
+                        MAX_REPORTED_MESSAGES
+                    )));
+                }
+                if self.messages.len() > MAX_EVIDENCE_MESSAGES {
+                    return Err(invalid(format!(
+                        "more than {} messages are sent as evidence",
+                        MAX_EVIDENCE_MESSAGES
+                    )));
+                }
+                let messages = self
+                    .messages
+                    .iter()
+                    .map(|(message, signature, reported)| {
+                        ReportedMessage::new(message, signature, *reported)
+                    })
+                    .collect::<Result<_>>()?;
+                Some(ReportEvidence { messages })
+            }
+            _ => None,
+        };
+
+        Ok(AbuseReport {
+            version: 1,
+            id: Uuid::new_v4(),
+            report: ReportBody {
+                report_type: self.report_type,
+                opinion_comments: self.comments.clone(),
+                reason,
+                evidence,
+                skin_url: self.skin_url.clone(),
+                reported_entity: ReportedEntity {
+                    profile_id: self.reported,
+                },
+                created_time: Utc::now(),
+            },
+            client_info: ClientInfo {
+                client_version,
+                locale: self.locale.clone(),
+            },
+            third_party_server_info: self
+                .server_address
+                .clone()
+                .map(|address| ThirdPartyServerInfo { address }),
+            realm_info: self.realm.clone(),
+        })
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::Report(ReportError::Invalid(message))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ReportErrorResponse {
+    #[serde(default)]
+    error: String,
+    #[serde(default)]
+    error_message: String,
+}
+
+/// Map a refused report to [`Error::Report`] if it was throttled or
+/// reporting is disabled, or to the API error otherwise.
+pub(crate) fn report_error(response: &HttpResponse) -> Error {
+    let status = response.status;
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Error::Report(ReportError::Throttled);
+    }
+
+    let message = response.json::<ReportErrorResponse>().unwrap_or_default();
+    let description = format!("{} {}", message.error, message.error_message).to_lowercase();
+
+    if description.contains("throttle") || description.contains("too many") {
+        Error::Report(ReportError::Throttled)
+    } else if description.contains("disabled") {
+        Error::Report(ReportError::Disabled)
+    } else if status == StatusCode::BAD_REQUEST && !message.error_message.is_empty() {
+        Error::Report(ReportError::Invalid(message.error_message))
+    } else {
+        response.error()
+    }
+}
@@ -0,0 +1,676 @@
+//! Minecraft services API
+//!
+//! The services API at `https://api.minecraftservices.com` manages the
+//! Minecraft profile of an account, authorized by its `access_token`.
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{IntoUrl, Method, StatusCode, Url};
+use serde::Serialize;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::certificates::{CertificatesResponse, PlayerCertificates};
+use crate::client::{Client, DEFAULT_CLIENT};
+use crate::consts::DEFAULT_SERVICES_SERVER;
+use crate::error::NameError;
+use crate::http::{HttpRequest, HttpResponse};
+use crate::report::{report_error, AbuseReport};
+use crate::{Error, Result};
+
+/// `ServicesClient` is used to make requests to the services API
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::services::{ServicesClient, SkinVariant};
+/// # use sage_auth::error::Result;
+/// # async fn anonymous(png: Vec<u8>) -> Result<()> {
+/// let client = ServicesClient::new("ACCESS_TOKEN");
+/// let profile = client.upload_skin(png, SkinVariant::Slim).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ServicesClient {
+    access_token: String,
+    server: Url,
+    client: Client,
+}
+
+/// Minecraft profile, as returned by the services API
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MinecraftProfile {
+    /// Profile identifier.
+    pub id: Uuid,
+
+    /// Profile name.
+    pub name: String,
+
+    /// Uploaded skins, the one in use is [`TextureState::Active`].
+    #[serde(default)]
+    pub skins: Vec<Skin>,
+
+    /// Owned capes, the one in use is [`TextureState::Active`].
+    #[serde(default)]
+    pub capes: Vec<Cape>,
+}
+
+/// A skin of a [`MinecraftProfile`]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Skin {
+    pub id: String,
+    pub state: TextureState,
+
+    /// URL of the skin texture.
+    pub url: String,
+    pub variant: SkinVariant,
+
+    /// Only present for the default skins, such as `STEVE`.
+    pub alias: Option<String>,
+}
+
+/// A cape of a [`MinecraftProfile`]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Cape {
+    pub id: String,
+    pub state: TextureState,
+
+    /// URL of the cape texture.
+    pub url: String,
+
+    /// Name of the cape, such as `Migrator`.
+    pub alias: Option<String>,
+}
+
+/// Whether the profile name can be changed, see
+/// [`ServicesClient::name_change_info`]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NameChangeInfo {
+    /// When the current name was set, absent if it was never changed.
+    pub changed_at: Option<DateTime<Utc>>,
+
+    /// When the profile was created.
+    pub created_at: DateTime<Utc>,
+
+    pub name_change_allowed: bool,
+}
+
+/// Result of [`ServicesClient::name_availability`]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum NameAvailability {
+    Available,
+
+    /// Used by another profile.
+    Duplicate,
+
+    /// Refused by the server, such as a blocked word.
+    NotAllowed,
+}
+
+/// Privileges and preferences of a player, see
+/// [`ServicesClient::player_attributes`]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerAttributes {
+    pub privileges: Privileges,
+    pub profanity_filter_preferences: ProfanityFilterPreferences,
+    #[serde(default)]
+    pub ban_status: BanStatus,
+}
+
+/// What a player is allowed to do
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Privileges {
+    pub online_chat: Privilege,
+    pub multiplayer_server: Privilege,
+    pub multiplayer_realms: Privilege,
+    pub telemetry: Privilege,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct Privilege {
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfanityFilterPreferences {
+    pub profanity_filter_on: bool,
+}
+
+/// Bans of a player
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BanStatus {
+    /// Bans by scope, such as `MULTIPLAYER`.
+    #[serde(default)]
+    pub banned_scopes: HashMap<String, BanDetails>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BanDetails {
+    pub ban_id: Uuid,
+
+    /// Milliseconds since the Unix epoch, absent for permanent bans.
+    pub expires: Option<i64>,
+
+    /// Reason code, such as `hate_speech`.
+    pub reason: String,
+
+    /// Message which can be shown to the user.
+    pub reason_message: Option<String>,
+}
+
+/// Products owned by an account, see [`ServicesClient::entitlements`]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Entitlements {
+    pub items: Vec<Entitlement>,
+
+    /// JWT signed by Mojang, holding the same entitlements.
+    pub signature: Option<String>,
+    pub key_id: Option<String>,
+}
+
+/// An owned product
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Entitlement {
+    /// Product name, such as `game_minecraft` or `product_minecraft`.
+    pub name: String,
+
+    /// How the product was obtained, such as `PURCHASE` or `GAMEPASS`.
+    /// Only present in the response of [`ServicesClient::license`].
+    pub source: Option<String>,
+
+    /// JWT signed by Mojang, only present in the response of
+    /// [`ServicesClient::entitlements`].
+    pub signature: Option<String>,
+}
+
+/// Whether a texture is in use
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TextureState {
+    Active,
+    Inactive,
+}
+
+/// Player model of a skin
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkinVariant {
+    /// The classic model with 4 pixel wide arms, known as Steve.
+    #[serde(rename = "classic", alias = "CLASSIC")]
+    Classic,
+
+    /// The slim model with 3 pixel wide arms, known as Alex.
+    #[serde(rename = "slim", alias = "SLIM")]
+    Slim,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NameAvailabilityResponse {
+    status: NameAvailability,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct NameErrorResponse {
+    details: Option<NameAvailabilityResponse>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PlayerAttributesParams {
+    profanity_filter_preferences: ProfanityFilterPreferences,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateProfileParams<'a> {
+    profile_name: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CapeParams<'a> {
+    cape_id: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SkinUrlParams<'a> {
+    variant: SkinVariant,
+    url: &'a str,
+}
+
+impl SkinVariant {
+    fn as_str(self) -> &'static str {
+        match self {
+            SkinVariant::Classic => "classic",
+            SkinVariant::Slim => "slim",
+        }
+    }
+}
+
+impl Entitlements {
+    /// Whether the product with `name` is owned.
+    pub fn contains(&self, name: &str) -> bool {
+        self.items.iter().any(|item| item.name == name)
+    }
+
+    /// Whether the account owns Minecraft: Java Edition, bought or through
+    /// Game Pass.
+    pub fn owns_minecraft(&self) -> bool {
+        self.contains("game_minecraft") || self.contains("product_minecraft")
+    }
+
+    /// Whether the account has Minecraft through Game Pass.
+    pub fn has_game_pass(&self) -> bool {
+        self.contains("product_game_pass_pc")
+            || self.contains("product_game_pass_ultimate")
+            || self
+                .items
+                .iter()
+                .any(|item| item.source.as_deref() == Some("GAMEPASS"))
+    }
+}
+
+impl PlayerAttributes {
+    /// Get the multiplayer ban of the player, if banned.
+    pub fn multiplayer_ban(&self) -> Option<&BanDetails> {
+        self.ban_status.banned_scopes.get("MULTIPLAYER")
+    }
+}
+
+impl ServicesClient {
+    /// Create a client authorized by `access_token`.
+    pub fn new<S: Into<String>>(access_token: S) -> ServicesClient {
+        ServicesClient {
+            access_token: access_token.into(),
+            server: (*DEFAULT_SERVICES_SERVER).clone(),
+            client: DEFAULT_CLIENT.clone(),
+        }
+    }
+
+    /// Set base url, default is `https://api.minecraftservices.com`.
+    pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut ServicesClient> {
+        self.server = server.into_url()?;
+        Ok(self)
+    }
+
+    /// Set client which sends the requests, default is [`Client::default`].
+    pub fn client(&mut self, client: &Client) -> &mut ServicesClient {
+        self.client = client.clone();
+        self
+    }
+
+    /// Get the Minecraft profile of the account.
+    pub async fn profile(&self) -> Result<MinecraftProfile> {
+        let response = self
+            .client
+            .send(self.request(Method::GET, "/minecraft/profile")?)
+            .await?;
+        profile_response(response)
+    }
+
+    /// Upload a skin PNG, which must be 64x64 or the legacy 64x32.
+    ///
+    /// The image is checked before uploading, returns
+    /// [`Error::InvalidSkin`] if it is not acceptable.
+    pub async fn upload_skin(
+        &self,
+        png: Vec<u8>,
+        variant: SkinVariant,
+    ) -> Result<MinecraftProfile> {
+        validate_skin(&png)?;
+
+        let boundary = format!("sage-auth-{}", Uuid::new_v4().to_simple());
+        let mut request = self.request(Method::POST, "/minecraft/profile/skins")?;
+        request.header(
+            CONTENT_TYPE,
+            &format!("multipart/form-data; boundary={}", boundary),
+        )?;
+        request.body = skin_form(&boundary, variant, &png);
+
+        let response = self.client.send(request).await?;
+        profile_response(response)
+    }
+
+    /// Use the skin at `url`.
+    pub async fn set_skin_url(&self, url: &str, variant: SkinVariant) -> Result<MinecraftProfile> {
+        let response = self
+            .client
+            .send(self.json_request(
+                Method::POST,
+                "/minecraft/profile/skins",
+                &SkinUrlParams { variant, url },
+            )?)
+            .await?;
+        profile_response(response)
+    }
+
+    /// Reset to the default skin.
+    pub async fn reset_skin(&self) -> Result<MinecraftProfile> {
+        let response = self
+            .client
+            .send(self.request(Method::DELETE, "/minecraft/profile/skins/active")?)
+            .await?;
+        profile_response(response)
+    }
+
+    /// List the owned capes.
+    pub async fn capes(&self) -> Result<Vec<Cape>> {
+        Ok(self.profile().await?.capes)
+    }
+
+    /// Show the cape with the given `cape_id`, see [`Cape::id`].
+    pub async fn equip_cape(&self, cape_id: &str) -> Result<MinecraftProfile> {
+        let response = self
+            .client
+            .send(self.json_request(
+                Method::PUT,
+                "/minecraft/profile/capes/active",
+                &CapeParams { cape_id },
+            )?)
+            .await?;
+        profile_response(response)
+    }
+
+    /// Hide the cape in use.
+    pub async fn hide_cape(&self) -> Result<MinecraftProfile> {
+        let response = self
+            .client
+            .send(self.request(Method::DELETE, "/minecraft/profile/capes/active")?)
+            .await?;
+        profile_response(response)
+    }
+
+    /// Get when the name was changed and whether it can be changed now.
+    pub async fn name_change_info(&self) -> Result<NameChangeInfo> {
+        let response = self
+            .client
+            .send(self.request(Method::GET, "/minecraft/profile/namechange")?)
+            .await?;
+
+        match response.status {
+            StatusCode::OK => Ok(response.json()?),
+            _ => Err(response.error()),
+        }
+    }
+
+    /// Check whether `name` can be used.
+    ///
+    /// Returns [`NameError::Invalid`] without making a request if `name`
+    /// is not valid, see [`validate_name`].
+    pub async fn name_availability(&self, name: &str) -> Result<NameAvailability> {
+        validate_name(name)?;
+
+        let endpoint = format!("/minecraft/profile/name/{}/available", name);
+        let response = self
+            .client
+            .send(self.request(Method::GET, &endpoint)?)
+            .await?;
+
+        match response.status {
+            StatusCode::OK => Ok(response.json::<NameAvailabilityResponse>()?.status),
+            _ => Err(response.error()),
+        }
+    }
+
+    /// Change the profile name to `name`.
+    ///
+    /// Returns [`Error::Name`] if the name is invalid, taken, or the name
+    /// was changed too recently. The last is checked with
+    /// [name_change_info](ServicesClient::name_change_info) when the server
+    /// refuses without a reason.
+    pub async fn change_name(&self, name: &str) -> Result<MinecraftProfile> {
+        validate_name(name)?;
+
+        let endpoint = format!("/minecraft/profile/name/{}", name);
+        let response = self
+            .client
+            .send(self.request(Method::PUT, &endpoint)?)
+            .await?;
+
+        match response.status {
+            StatusCode::BAD_REQUEST | StatusCode::FORBIDDEN => match name_error(&response) {
+                Some(error) => Err(error),
+                None => match self.name_change_info().await {
+                    Ok(info) if !info.name_change_allowed => Err(Error::Name(NameError::TooSoon)),
+                    _ => Err(response.error()),
+                },
+            },
+            _ => profile_response(response),
+        }
+    }
+
+    /// Create the Minecraft profile of an account which owns the game but
+    /// has no profile yet, see
+    /// [AccountState::NoProfile](crate::session::AccountState::NoProfile).
+    ///
+    /// The availability of `name` is checked first, returns
+    /// [`Error::Name`] if it is invalid or taken.
+    pub async fn create_profile(&self, name: &str) -> Result<MinecraftProfile> {
+        match self.name_availability(name).await? {
+            NameAvailability::Available => (),
+            NameAvailability::Duplicate => return Err(Error::Name(NameError::Taken)),
+            NameAvailability::NotAllowed => return Err(Error::Name(NameError::Invalid)),
+        }
+
+        let response = self
+            .client
+            .send(self.json_request(
+                Method::POST,
+                "/minecraft/profile",
+                &CreateProfileParams { profile_name: name },
+            )?)
+            .await?;
+
+        match response.status {
+            StatusCode::BAD_REQUEST | StatusCode::FORBIDDEN => {
+                Err(name_error(&response).unwrap_or_else(|| response.error()))
+            }
+            _ => profile_response(response),
+        }
+    }
+
+    /// Request a new chat signing key pair.
+    ///
+    /// [Session::player_certificates](crate::session::Session::player_certificates)
+    /// caches the key pair and refreshes it when needed.
+    pub async fn player_certificates(&self) -> Result<PlayerCertificates> {
+        let response = self
+            .client
+            .send(self.request(Method::POST, "/player/certificates")?)
+            .await?;
+
+        match response.status {
+            StatusCode::OK => {
+                PlayerCertificates::from_response(response.json::<CertificatesResponse>()?)
+            }
+            _ => Err(response.error()),
+        }
+    }
+
+    /// Get the owned products from `/entitlements/mcstore`.
+    pub async fn entitlements(&self) -> Result<Entitlements> {
+        let response = self
+            .client
+            .send(self.request(Method::GET, "/entitlements/mcstore")?)
+            .await?;
+        entitlements_response(response)
+    }
+
+    /// Get the owned products and how they were obtained from
+    /// `/entitlements/license`.
+    pub async fn license(&self) -> Result<Entitlements> {
+        let endpoint = format!("/entitlements/license?requestId={}", Uuid::new_v4());
+        let response = self
+            .client
+            .send(self.request(Method::GET, &endpoint)?)
+            .await?;
+        entitlements_response(response)
+    }
+
+    /// Get the privileges, profanity filter preferences and ban status of
+    /// the player.
+    pub async fn player_attributes(&self) -> Result<PlayerAttributes> {
+        let response = self
+            .client
+            .send(self.request(Method::GET, "/player/attributes")?)
+            .await?;
+        attributes_response(response)
+    }
+
+    /// Turn the profanity filter on or off.
+    pub async fn set_profanity_filter(
+        &self,
+        profanity_filter_on: bool,
+    ) -> Result<PlayerAttributes> {
+        let params = PlayerAttributesParams {
+            profanity_filter_preferences: ProfanityFilterPreferences {
+                profanity_filter_on,
+            },
+        };
+        let response = self
+            .client
+            .send(self.json_request(Method::POST, "/player/attributes", &params)?)
+            .await?;
+        attributes_response(response)
+    }
+
+    /// Submit an abuse report, see [ReportBuilder](crate::report::ReportBuilder).
+    ///
+    /// Returns [`Error::Report`] if too many reports were submitted or
+    /// reporting is disabled.
+    pub async fn report(&self, report: &AbuseReport) -> Result<()> {
+        let response = self
+            .client
+            .send(self.json_request(Method::POST, "/player/report", report)?)
+            .await?;
+
+        match response.status {
+            StatusCode::OK | StatusCode::NO_CONTENT => Ok(()),
+            _ => Err(report_error(&response)),
+        }
+    }
+
+    fn request(&self, method: Method, endpoint: &str) -> Result<HttpRequest> {
+        let mut request = HttpRequest::new(method, self.server.join(endpoint)?);
+        self.authorize(&mut request)?;
+        Ok(request)
+    }
+
+    fn json_request<T: Serialize>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: &T,
+    ) -> Result<HttpRequest> {
+        let mut request = HttpRequest::json(method, self.server.join(endpoint)?, body)?;
+        self.authorize(&mut request)?;
+        Ok(request)
+    }
+
+    fn authorize(&self, request: &mut HttpRequest) -> Result<()> {
+        request.header(AUTHORIZATION, &format!("Bearer {}", self.access_token))?;
+        Ok(())
+    }
+}
+
+fn profile_response(response: HttpResponse) -> Result<MinecraftProfile> {
+    match response.status {
+        StatusCode::OK => Ok(response.json()?),
+        _ => Err(response.error()),
+    }
+}
+
+/// Encode the form of a skin upload. The body is built in memory rather
+/// than streamed, so that any [Transport](crate::transport::Transport) can
+/// send it.
+fn skin_form(boundary: &str, variant: SkinVariant, png: &[u8]) -> Vec<u8> {
+    let mut body = format!(
+        "--{boundary}\r\n\
+         Content-Disposition: form-data; name=\"variant\"\r\n\r\n\
+         {variant}\r\n\
+         --{boundary}\r\n\
+         Content-Disposition: form-data; name=\"file\"; filename=\"skin.png\"\r\n\
+         Content-Type: image/png\r\n\r\n",
+        boundary = boundary,
+        variant = variant.as_str(),
+    )
+    .into_bytes();
+    body.extend_from_slice(png);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// Map a refused name to [`Error::Name`] by the `details.status` of the
+/// response. A `400 Bad Request` without one is an invalid name, other
+/// responses without one are not about the name.
+fn name_error(response: &HttpResponse) -> Option<Error> {
+    let body = response.json::<NameErrorResponse>().unwrap_or_default();
+
+    match body.details.map(|details| details.status) {
+        Some(NameAvailability::Duplicate) => Some(Error::Name(NameError::Taken)),
+        Some(NameAvailability::NotAllowed) => Some(Error::Name(NameError::Invalid)),
+        _ if response.status == StatusCode::BAD_REQUEST => Some(Error::Name(NameError::Invalid)),
+        _ => None,
+    }
+}
+
+fn entitlements_response(response: HttpResponse) -> Result<Entitlements> {
+    match response.status {
+        StatusCode::OK => Ok(response.json()?),
+        _ => Err(response.error()),
+    }
+}
+
+fn attributes_response(response: HttpResponse) -> Result<PlayerAttributes> {
+    match response.status {
+        StatusCode::OK => Ok(response.json()?),
+        _ => Err(response.error()),
+    }
+}
+
+/// Check that `png` is a PNG image with a valid skin size, 64x64 or the
+/// legacy 64x32.
+pub fn validate_skin(png: &[u8]) -> Result<()> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    // The IHDR chunk is always first: length, type, width and height.
+    if png.len() < 24 || &png[..8] != SIGNATURE || &png[12..16] != b"IHDR" {
+        return Err(Error::InvalidSkin("not a PNG image".to_owned()));
+    }
+
+    let width = u32::from_be_bytes([png[16], png[17], png[18], png[19]]);
+    let height = u32::from_be_bytes([png[20], png[21], png[22], png[23]]);
+    match (width, height) {
+        (64, 64) | (64, 32) => Ok(()),
+        _ => Err(Error::InvalidSkin(format!(
+            "skin must be 64x64 or 64x32, got {}x{}",
+            width, height
+        ))),
+    }
+}
+
+/// Check that `name` is a valid profile name, 3 to 16 characters of
+/// `[A-Za-z0-9_]`.
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid_chars = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if (3..=16).contains(&name.len()) && valid_chars {
+        Ok(())
+    } else {
+        Err(Error::Name(NameError::Invalid))
+    }
+}
@@ -1,11 +1,20 @@
-use reqwest::StatusCode;
+use reqwest::{IntoUrl, Method, Url};
 use serde_derive::Serialize;
 use uuid::Uuid;
 
 use crate::auth::AuthenticateBuilder;
+use crate::certificates::PlayerCertificates;
+use crate::client::{Client, DEFAULT_CLIENT};
+use crate::consts::{DEFAULT_SERVER, DEFAULT_SERVICES_SERVER, DEFAULT_SESSION_SERVER};
 use crate::error::{Error, Result};
+use crate::http::{HttpRequest, HttpResponse};
+use crate::launch::{offline_uuid, LaunchArguments};
+use crate::pool::ServerPool;
+use crate::realms::RealmsClient;
 use crate::refresh::RefreshBuilder;
+use crate::services::ServicesClient;
 use crate::types::{serialize_uuid_simple, Profile};
+use crate::validate::ValidateBuilder;
 
 /// A Minecraft authentication session with `access_token` and `client_token`
 /// saved.
@@ -13,7 +22,26 @@ use crate::types::{serialize_uuid_simple, Profile};
 pub struct Session {
     access_token: String,
     client_token: Uuid,
-    profile: Profile,
+    profile: Option<Profile>,
+    certificates: Option<PlayerCertificates>,
+    client: Client,
+    auth_server: Url,
+    session_server: Url,
+    services_server: Url,
+}
+
+/// Whether an account can play Minecraft, see [Session::account_state]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountState {
+    /// The account owns the game and has a profile.
+    Owned,
+
+    /// The account does not own the game, and can only play the demo. It
+    /// may still have a profile, such as when its Game Pass expired.
+    Demo,
+
+    /// The account owns the game, but has not created a profile yet.
+    NoProfile,
 }
 
 #[derive(Debug, Serialize)]
@@ -43,19 +71,52 @@ impl Session {
     pub async fn from_token<S: Into<String>>(
         access_token: S,
         client_token: Uuid,
+    ) -> Result<Session> {
+        Session::from_token_with_client(&DEFAULT_CLIENT, access_token, client_token).await
+    }
+
+    /// Use saved `access_token` to create a session, which sends its
+    /// requests with `client`.
+    pub async fn from_token_with_client<S: Into<String>>(
+        client: &Client,
+        access_token: S,
+        client_token: Uuid,
+    ) -> Result<Session> {
+        let pool = ServerPool::single((*DEFAULT_SERVER).clone());
+        Session::from_token_with_pool(client, &pool, access_token, client_token).await
+    }
+
+    /// Use saved `access_token` to create a session on the first server of
+    /// `pool` which answers. The session stays on that server.
+    pub async fn from_token_with_pool<S: Into<String>>(
+        client: &Client,
+        pool: &ServerPool,
+        access_token: S,
+        client_token: Uuid,
     ) -> Result<Session> {
         let access_token = access_token.into();
 
-        let refresh_response = RefreshBuilder::new()
+        let mut builder = RefreshBuilder::new();
+        let refresh_response = builder
+            .client(client)
+            .servers(pool)
             .access_token(&access_token)
             .client_token(client_token)
             .request()
             .await?;
 
         Ok(Session {
-            access_token,
+            access_token: refresh_response.access_token,
             client_token,
-            profile: refresh_response.selected_profile.unwrap(),
+            profile: refresh_response.selected_profile,
+            certificates: None,
+            client: client.clone(),
+            auth_server: builder
+                .answered_server()
+                .unwrap_or(&pool.servers()[0])
+                .clone(),
+            session_server: (*DEFAULT_SESSION_SERVER).clone(),
+            services_server: (*DEFAULT_SERVICES_SERVER).clone(),
         })
     }
 
@@ -70,7 +131,44 @@ impl Session {
     /// # }
     /// ```
     pub async fn login(username: &str, password: &str) -> Result<Session> {
-        let auth_response = AuthenticateBuilder::new()
+        Session::login_with_client(&DEFAULT_CLIENT, username, password).await
+    }
+
+    /// Login to create a session, which sends its requests with `client`.
+    ///
+    /// ```no_run
+    /// # use sage_auth::client::{Client, RetryPolicy};
+    /// # use sage_auth::session::Session;
+    /// # use sage_auth::error::Result;
+    /// # async fn anonymous() -> Result<()> {
+    /// let mut client = Client::new();
+    /// client.retry_policy(RetryPolicy::none());
+    ///
+    /// let session = Session::login_with_client(&client, "USERNAME", "PASSWORD").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn login_with_client(
+        client: &Client,
+        username: &str,
+        password: &str,
+    ) -> Result<Session> {
+        let pool = ServerPool::single((*DEFAULT_SERVER).clone());
+        Session::login_with_pool(client, &pool, username, password).await
+    }
+
+    /// Login on the first server of `pool` which answers, see [ServerPool].
+    /// The session stays on that server.
+    pub async fn login_with_pool(
+        client: &Client,
+        pool: &ServerPool,
+        username: &str,
+        password: &str,
+    ) -> Result<Session> {
+        let mut builder = AuthenticateBuilder::new();
+        let auth_response = builder
+            .client(client)
+            .servers(pool)
             .username(username)
             .password(password)
             .request()
@@ -79,10 +177,66 @@ impl Session {
         Ok(Session {
             access_token: auth_response.access_token,
             client_token: auth_response.client_token,
-            profile: auth_response.selected_profile.unwrap(),
+            profile: auth_response.selected_profile,
+            certificates: None,
+            client: client.clone(),
+            auth_server: builder
+                .answered_server()
+                .unwrap_or(&pool.servers()[0])
+                .clone(),
+            session_server: (*DEFAULT_SESSION_SERVER).clone(),
+            services_server: (*DEFAULT_SERVICES_SERVER).clone(),
         })
     }
 
+    /// Check that the `access_token` is still valid, on the server where
+    /// the session was created.
+    pub async fn validate(&self) -> Result<()> {
+        ValidateBuilder::new()
+            .client(&self.client)
+            .server(self.auth_server.clone())?
+            .access_token(&self.access_token)
+            .client_token(self.client_token)
+            .request()
+            .await
+    }
+
+    /// Replace the `access_token` with a new one, on the server where the
+    /// session was created.
+    pub async fn refresh(&mut self) -> Result<()> {
+        let refresh_response = RefreshBuilder::new()
+            .client(&self.client)
+            .server(self.auth_server.clone())?
+            .access_token(&self.access_token)
+            .client_token(self.client_token)
+            .request()
+            .await?;
+
+        self.access_token = refresh_response.access_token;
+        if refresh_response.selected_profile.is_some() {
+            self.profile = refresh_response.selected_profile;
+        }
+        Ok(())
+    }
+
+    /// Get the authentication server which the session is on.
+    pub fn auth_server(&self) -> &Url {
+        &self.auth_server
+    }
+
+    /// Set session server, default is `https://sessionserver.mojang.com`.
+    pub fn session_server<T: IntoUrl>(&mut self, server: T) -> Result<&mut Session> {
+        self.session_server = server.into_url()?;
+        Ok(self)
+    }
+
+    /// Set services server of [services](Session::services), default is
+    /// `https://api.minecraftservices.com`.
+    pub fn services_server<T: IntoUrl>(&mut self, server: T) -> Result<&mut Session> {
+        self.services_server = server.into_url()?;
+        Ok(self)
+    }
+
     /// Post a join message to Mojang session server.
     ///
     /// Note: `server_id_hash` is generated by a special hash function,
@@ -100,23 +254,85 @@ impl Session {
     /// # }
     /// ```
     pub async fn join_server(&self, server_id_hash: &str) -> Result<()> {
-        let request = JoinRequest {
-            access_token: &self.access_token,
-            selected_profile: self.profile.id,
-            server_id: server_id_hash,
-        };
+        let request = self.join_request(server_id_hash)?;
+        let response = self.client.send_http(request, true).await?;
+        parse_join_response(&response)
+    }
 
-        let client = reqwest::Client::new();
-        let response = client
-            .post("https://sessionserver.mojang.com/session/minecraft/join")
-            .json(&request)
-            .send()
-            .await?;
+    /// Build the request of [join_server](Session::join_server) without
+    /// sending it, see [join_request].
+    pub fn join_request(&self, server_id_hash: &str) -> Result<HttpRequest> {
+        let profile = self
+            .profile
+            .as_ref()
+            .ok_or(Error::MissingField("selected_profile"))?;
+        join_request(
+            &self.access_token,
+            profile.id,
+            server_id_hash,
+            &self.session_server,
+        )
+    }
 
-        match response.status() {
-            StatusCode::NO_CONTENT => Ok(()),
-            _ => Err(Error::from_response(response).await),
+    /// Get the chat signing key pair.
+    ///
+    /// The key pair is requested on first use and cached, a new one is
+    /// requested once Mojang asks to refresh it. If that request fails, the
+    /// cached key pair is still used until it expires.
+    ///
+    /// ```no_run
+    /// # use sage_auth::session::Session;
+    /// # use sage_auth::error::Result;
+    /// # async fn anonymous() -> Result<()> {
+    /// let mut session = Session::login("USERNAME", "PASSWORD").await?;
+    /// let public_key = session.player_certificates().await?.public_key_data()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn player_certificates(&mut self) -> Result<&PlayerCertificates> {
+        let stale = match &self.certificates {
+            Some(certificates) => certificates.needs_refresh(),
+            None => true,
+        };
+        if stale {
+            match self.services().player_certificates().await {
+                Ok(certificates) => self.certificates = Some(certificates),
+                Err(error) => match &self.certificates {
+                    Some(certificates) if !certificates.is_expired() => (),
+                    _ => return Err(error),
+                },
+            }
         }
+
+        Ok(self.certificates.as_ref().unwrap())
+    }
+
+    /// Create the Minecraft profile of an account which owns the game but
+    /// has no profile yet, and select it.
+    ///
+    /// Returns [`Error::Name`] if `name` is invalid or taken.
+    ///
+    /// ```no_run
+    /// # use sage_auth::session::{AccountState, Session};
+    /// # use sage_auth::error::Result;
+    /// # async fn anonymous() -> Result<()> {
+    /// let mut session = Session::login("USERNAME", "PASSWORD").await?;
+    /// if session.account_state().await? == AccountState::NoProfile {
+    ///     session.create_profile("NAME").await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_profile(&mut self, name: &str) -> Result<&Profile> {
+        let created = self.services().create_profile(name).await?;
+
+        Ok(self.profile.insert(Profile {
+            agent: None,
+            id: created.id,
+            name: created.name,
+            legacy: false,
+            properties: Vec::new(),
+        }))
     }
 
     /// Get `access_token`
@@ -128,4 +344,129 @@ impl Session {
     pub fn client_token(&self) -> Uuid {
         self.client_token
     }
+
+    /// Get the selected profile, absent if the account has no profile.
+    /// See [account_state](Session::account_state) for why.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Check whether the account can play, by its profile and its
+    /// entitlements from the services API.
+    ///
+    /// ```no_run
+    /// # use sage_auth::session::{AccountState, Session};
+    /// # use sage_auth::error::Result;
+    /// # async fn anonymous() -> Result<()> {
+    /// let session = Session::login("USERNAME", "PASSWORD").await?;
+    /// if session.account_state().await? == AccountState::Demo {
+    ///     // Offer the demo
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn account_state(&self) -> Result<AccountState> {
+        if !self.services().entitlements().await?.owns_minecraft() {
+            return Ok(AccountState::Demo);
+        }
+
+        match self.profile {
+            Some(_) => Ok(AccountState::Owned),
+            None => Ok(AccountState::NoProfile),
+        }
+    }
+
+    /// Create a [ServicesClient] authorized by the `access_token` of the
+    /// session, to manage skins and the rest of the Minecraft profile.
+    ///
+    /// ```no_run
+    /// # use sage_auth::session::Session;
+    /// # use sage_auth::error::Result;
+    /// # async fn anonymous() -> Result<()> {
+    /// let session = Session::login("USERNAME", "PASSWORD").await?;
+    /// let profile = session.services().reset_skin().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn services(&self) -> ServicesClient {
+        let mut services = ServicesClient::new(self.access_token.as_str());
+        services
+            .client(&self.client)
+            .server(self.services_server.clone())
+            .expect("the services server was already parsed");
+        services
+    }
+
+    /// Get a client for the Realms API, playing `version` of the game.
+    ///
+    /// Returns [`Error::MissingField`] if the account has no profile.
+    pub fn realms(&self, version: &str) -> Result<RealmsClient> {
+        let profile = self
+            .profile
+            .as_ref()
+            .ok_or(Error::MissingField("selected_profile"))?;
+        let mut realms = RealmsClient::new(&self.access_token, profile.id, &profile.name, version);
+        realms.client(&self.client);
+        Ok(realms)
+    }
+
+    /// Get the [LaunchArguments] of the selected profile, without making
+    /// a request.
+    ///
+    /// The user type is left as `mojang`, which a launcher can change with
+    /// [LaunchArguments::user_type](crate::launch::LaunchArguments::user_type).
+    /// Returns [`Error::MissingField`] if the account has no profile.
+    pub fn launch_arguments(&self) -> Result<LaunchArguments> {
+        let profile = self
+            .profile
+            .as_ref()
+            .ok_or(Error::MissingField("selected_profile"))?;
+        let mut arguments = LaunchArguments::new(&*profile.name, profile.id, &*self.access_token);
+        arguments.client_id(self.client_token.to_simple().to_string());
+        Ok(arguments)
+    }
+
+    /// Get the [LaunchArguments] of the account after checking its
+    /// [account_state](Session::account_state), so that accounts which only
+    /// have the demo play it as `Player`.
+    ///
+    /// The check asks the services API, which does not accept the tokens of
+    /// third-party servers; use [launch_arguments](Session::launch_arguments)
+    /// for those.
+    pub async fn launch_arguments_checked(&self) -> Result<LaunchArguments> {
+        if self.account_state().await? != AccountState::Demo {
+            return self.launch_arguments();
+        }
+
+        let mut arguments =
+            LaunchArguments::new("Player", offline_uuid("Player"), &*self.access_token);
+        arguments
+            .demo(true)
+            .client_id(self.client_token.to_simple().to_string());
+        Ok(arguments)
+    }
+}
+
+/// Build a join request of the profile with `profile_id` to
+/// `session_server`, such as `https://sessionserver.mojang.com`, without a
+/// [Session]. See [http](crate::http).
+pub fn join_request(
+    access_token: &str,
+    profile_id: Uuid,
+    server_id_hash: &str,
+    session_server: &Url,
+) -> Result<HttpRequest> {
+    let request = JoinRequest {
+        access_token,
+        selected_profile: profile_id,
+        server_id: server_id_hash,
+    };
+
+    let url = session_server.join("/session/minecraft/join")?;
+    HttpRequest::json(Method::POST, url, &request)
+}
+
+/// Read the response of a join request, see [join_request].
+pub fn parse_join_response(response: &HttpResponse) -> Result<()> {
+    response.no_content()
 }
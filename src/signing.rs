@@ -0,0 +1,325 @@
+//! Texture signing keys
+//!
+//! A session server signs profile properties with `SHA1withRSA`, and
+//! publishes the public key so that clients and servers can check the
+//! signatures. Keys are loaded and saved as PEM or DER, and the public key
+//! is published in the format authlib-injector expects in
+//! `signaturePublickey`.
+//!
+//! The same key types hold a player's chat key pair, which signs with
+//! `SHA256withRSA` instead, see [chat](crate::chat).
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rsa::pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey};
+use rsa::pkcs1v15;
+use rsa::pkcs8::{
+    DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding,
+};
+use rsa::rand_core::OsRng;
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use sha1::Sha1;
+use sha2::Sha256;
+use std::cmp::Reverse;
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime};
+
+use crate::types::{Profile, Property};
+use crate::{Error, Result};
+
+/// RSA private key used to sign profile properties.
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::signing::SigningKey;
+/// # use sage_auth::error::Result;
+/// # fn anonymous() -> Result<()> {
+/// let key = SigningKey::generate(4096)?;
+/// let signature_publickey = key.public_key().to_pem()?;
+/// let property = key.sign_property("textures", "eyJ0aW1lc3RhbXAiOjB9");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SigningKey {
+    key: RsaPrivateKey,
+}
+
+/// RSA public key used to verify signatures on profile properties.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicKey {
+    key: RsaPublicKey,
+}
+
+/// Signing keys with overlapping validity, used to rotate keys.
+///
+/// New properties are signed by the newest valid key, while signatures made
+/// by any valid key are accepted. Publish [`public_keys`](KeyRing::public_keys)
+/// so that clients trust every key that may still be in use.
+#[derive(Default)]
+pub struct KeyRing {
+    keys: Vec<RingKey>,
+}
+
+struct RingKey {
+    key: SigningKey,
+    not_before: SystemTime,
+    not_after: Option<SystemTime>,
+}
+
+impl SigningKey {
+    /// Generate a new key with `bits` bits, authlib-injector recommends 4096.
+    pub fn generate(bits: usize) -> Result<SigningKey> {
+        let key = RsaPrivateKey::new(&mut OsRng, bits).map_err(key_error)?;
+        Ok(SigningKey { key })
+    }
+
+    /// Load a PKCS#8 (`PRIVATE KEY`) or PKCS#1 (`RSA PRIVATE KEY`) PEM.
+    pub fn from_pem(pem: &str) -> Result<SigningKey> {
+        let key = RsaPrivateKey::from_pkcs8_pem(pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(pem))
+            .map_err(key_error)?;
+        Ok(SigningKey { key })
+    }
+
+    /// Load a PKCS#8 or PKCS#1 DER.
+    pub fn from_der(der: &[u8]) -> Result<SigningKey> {
+        let key = RsaPrivateKey::from_pkcs8_der(der)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_der(der))
+            .map_err(key_error)?;
+        Ok(SigningKey { key })
+    }
+
+    /// Save as a PKCS#8 PEM.
+    pub fn to_pem(&self) -> Result<String> {
+        let pem = self.key.to_pkcs8_pem(LineEnding::LF).map_err(key_error)?;
+        Ok(pem.to_string())
+    }
+
+    /// Save as a PKCS#8 DER.
+    pub fn to_der(&self) -> Result<Vec<u8>> {
+        let der = self.key.to_pkcs8_der().map_err(key_error)?;
+        Ok(der.as_bytes().to_vec())
+    }
+
+    /// Get the public half of the key.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey {
+            key: self.key.to_public_key(),
+        }
+    }
+
+    /// Sign `data`, returns the Base64 encoded signature which Java's
+    /// `SHA1withRSA` accepts.
+    pub fn sign(&self, data: &[u8]) -> String {
+        BASE64.encode(self.sign_bytes(data))
+    }
+
+    /// Sign `data` with `SHA1withRSA`, returns the raw signature.
+    pub fn sign_bytes(&self, data: &[u8]) -> Vec<u8> {
+        let signer = pkcs1v15::SigningKey::<Sha1>::new(self.key.clone());
+        signer.sign(data).to_vec()
+    }
+
+    /// Sign `data` with `SHA256withRSA`, as chat messages are signed.
+    pub fn sign_sha256_bytes(&self, data: &[u8]) -> Vec<u8> {
+        let signer = pkcs1v15::SigningKey::<Sha256>::new(self.key.clone());
+        signer.sign(data).to_vec()
+    }
+
+    /// Create a signed property.
+    pub fn sign_property<N: Into<String>, V: Into<String>>(&self, name: N, value: V) -> Property {
+        let value = value.into();
+        Property {
+            name: name.into(),
+            signature: Some(self.sign(value.as_bytes())),
+            value,
+        }
+    }
+}
+
+impl PublicKey {
+    /// Load a `PUBLIC KEY` PEM, such as authlib-injector's `signaturePublickey`.
+    pub fn from_pem(pem: &str) -> Result<PublicKey> {
+        let key = RsaPublicKey::from_public_key_pem(pem).map_err(key_error)?;
+        Ok(PublicKey { key })
+    }
+
+    /// Load a X.509 `SubjectPublicKeyInfo` or PKCS#1 DER.
+    pub fn from_der(der: &[u8]) -> Result<PublicKey> {
+        let key = RsaPublicKey::from_public_key_der(der)
+            .or_else(|_| RsaPublicKey::from_pkcs1_der(der))
+            .map_err(key_error)?;
+        Ok(PublicKey { key })
+    }
+
+    /// Save as a `PUBLIC KEY` PEM, the format of authlib-injector's
+    /// `signaturePublickey`.
+    pub fn to_pem(&self) -> Result<String> {
+        self.key
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(key_error)
+    }
+
+    /// Save as a X.509 `SubjectPublicKeyInfo` DER.
+    pub fn to_der(&self) -> Result<Vec<u8>> {
+        let der = self.key.to_public_key_der().map_err(key_error)?;
+        Ok(der.as_bytes().to_vec())
+    }
+
+    /// Verify a Base64 encoded `SHA1withRSA` signature of `data`.
+    pub fn verify(&self, data: &[u8], signature: &str) -> Result<()> {
+        let signature = BASE64
+            .decode(signature)
+            .map_err(|_| Error::InvalidSignature)?;
+        self.verify_bytes(data, &signature)
+    }
+
+    /// Verify a raw `SHA1withRSA` signature of `data`.
+    pub fn verify_bytes(&self, data: &[u8], signature: &[u8]) -> Result<()> {
+        let signature =
+            pkcs1v15::Signature::try_from(signature).map_err(|_| Error::InvalidSignature)?;
+        pkcs1v15::VerifyingKey::<Sha1>::new(self.key.clone())
+            .verify(data, &signature)
+            .map_err(|_| Error::InvalidSignature)
+    }
+
+    /// Verify a raw `SHA256withRSA` signature of `data`, as chat messages
+    /// are signed.
+    pub fn verify_sha256_bytes(&self, data: &[u8], signature: &[u8]) -> Result<()> {
+        let signature =
+            pkcs1v15::Signature::try_from(signature).map_err(|_| Error::InvalidSignature)?;
+        pkcs1v15::VerifyingKey::<Sha256>::new(self.key.clone())
+            .verify(data, &signature)
+            .map_err(|_| Error::InvalidSignature)
+    }
+
+    /// Verify the signature of a property. Unsigned properties are rejected.
+    pub fn verify_property(&self, property: &Property) -> Result<()> {
+        match &property.signature {
+            Some(signature) => self.verify(property.value.as_bytes(), signature),
+            None => Err(Error::InvalidSignature),
+        }
+    }
+
+    /// Verify every property of a profile fetched with `unsigned=false`.
+    pub fn verify_profile(&self, profile: &Profile) -> Result<()> {
+        profile
+            .properties
+            .iter()
+            .try_for_each(|property| self.verify_property(property))
+    }
+}
+
+impl KeyRing {
+    pub fn new() -> KeyRing {
+        KeyRing::default()
+    }
+
+    /// Add a key which is valid from `not_before`, and until `not_after` if set.
+    pub fn add(
+        &mut self,
+        key: SigningKey,
+        not_before: SystemTime,
+        not_after: Option<SystemTime>,
+    ) -> &mut KeyRing {
+        self.keys.push(RingKey {
+            key,
+            not_before,
+            not_after,
+        });
+        self
+    }
+
+    /// Start signing with `key` now, and keep accepting the keys which are
+    /// currently valid for `overlap` longer.
+    pub fn rotate(&mut self, key: SigningKey, overlap: Duration) -> &mut KeyRing {
+        let now = SystemTime::now();
+        let retire_at = now + overlap;
+        for old in self.keys.iter_mut().filter(|old| old.is_valid_at(now)) {
+            if old.not_after.is_none_or(|not_after| not_after > retire_at) {
+                old.not_after = Some(retire_at);
+            }
+        }
+        self.add(key, now, None)
+    }
+
+    /// Drop keys which have expired.
+    pub fn prune(&mut self) -> &mut KeyRing {
+        let now = SystemTime::now();
+        self.keys
+            .retain(|key| key.not_after.is_none_or(|not_after| now < not_after));
+        self
+    }
+
+    /// Get the newest valid key, which new properties should be signed with.
+    pub fn signing_key(&self) -> Option<&SigningKey> {
+        let now = SystemTime::now();
+        self.keys
+            .iter()
+            .filter(|key| key.is_valid_at(now))
+            .max_by_key(|key| key.not_before)
+            .map(|key| &key.key)
+    }
+
+    /// Create a property signed by the [`signing_key`](KeyRing::signing_key).
+    pub fn sign_property<N: Into<String>, V: Into<String>>(
+        &self,
+        name: N,
+        value: V,
+    ) -> Result<Property> {
+        match self.signing_key() {
+            Some(key) => Ok(key.sign_property(name, value)),
+            None => Err(Error::Key("no valid signing key".to_owned())),
+        }
+    }
+
+    /// Get the public keys of every valid key, newest first.
+    pub fn public_keys(&self) -> Vec<PublicKey> {
+        let now = SystemTime::now();
+        let mut keys: Vec<_> = self
+            .keys
+            .iter()
+            .filter(|key| key.is_valid_at(now))
+            .collect();
+        keys.sort_by_key(|key| Reverse(key.not_before));
+        keys.into_iter().map(|key| key.key.public_key()).collect()
+    }
+
+    /// Verify a property against every valid key.
+    pub fn verify_property(&self, property: &Property) -> Result<()> {
+        if self
+            .public_keys()
+            .iter()
+            .any(|key| key.verify_property(property).is_ok())
+        {
+            Ok(())
+        } else {
+            Err(Error::InvalidSignature)
+        }
+    }
+}
+
+impl RingKey {
+    fn is_valid_at(&self, time: SystemTime) -> bool {
+        self.not_before <= time && self.not_after.is_none_or(|not_after| time < not_after)
+    }
+}
+
+/// Decode the body of a PEM, ignoring its label.
+///
+/// Mojang labels PKCS#8 and X.509 keys as `RSA PRIVATE KEY` and
+/// `RSA PUBLIC KEY`, so the label cannot be trusted.
+pub(crate) fn pem_to_der(pem: &str) -> Result<Vec<u8>> {
+    let body: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .flat_map(|line| line.chars().filter(|c| !c.is_whitespace()))
+        .collect();
+    BASE64.decode(body).map_err(key_error)
+}
+
+fn key_error<E: std::fmt::Display>(error: E) -> Error {
+    Error::Key(error.to_string())
+}
@@ -1,9 +1,12 @@
 //! Signout request
 
-use reqwest::{IntoUrl, StatusCode, Url};
+use reqwest::{IntoUrl, Method, Url};
 use serde_derive::Serialize;
 
+use crate::client::{Client, DEFAULT_CLIENT};
 use crate::consts::DEFAULT_SERVER;
+use crate::http::{HttpRequest, HttpResponse};
+use crate::pool::ServerPool;
 use crate::{Error, Result};
 
 #[derive(Serialize)]
@@ -31,10 +34,13 @@ struct SignoutParams<'a> {
 /// ```
 pub struct SignoutBuilder<'a> {
     params: SignoutParams<'a>,
-    server: Url,
+    servers: ServerPool,
     endpoint: &'a str,
+    client: Client,
+    answered: Option<Url>,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for SignoutParams<'_> {
     fn default() -> SignoutParams<'static> {
         SignoutParams {
@@ -48,8 +54,10 @@ impl Default for SignoutBuilder<'_> {
     fn default() -> SignoutBuilder<'static> {
         SignoutBuilder {
             params: SignoutParams::default(),
-            server: (*DEFAULT_SERVER).clone(),
+            servers: ServerPool::single((*DEFAULT_SERVER).clone()),
             endpoint: "/signout",
+            client: DEFAULT_CLIENT.clone(),
+            answered: None,
         }
     }
 }
@@ -73,19 +81,57 @@ impl<'a> SignoutBuilder<'a> {
 
     /// Set base url, default is `https://authserver.mojang.com`.
     pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut SignoutBuilder<'a>> {
-        self.server = server.into_url()?;
+        self.servers = ServerPool::single(server.into_url()?);
         Ok(self)
     }
 
+    /// Set mirrored servers to fail over between, instead of a single
+    /// [server](Self::server).
+    pub fn servers(&mut self, servers: &ServerPool) -> &mut SignoutBuilder<'a> {
+        self.servers = servers.clone();
+        self
+    }
+
     /// set endpoint, default is `/authenticate`.
     pub fn endpoint(&mut self, endpoint: &'a str) -> &mut SignoutBuilder<'a> {
         self.endpoint = endpoint;
         self
     }
 
+    /// Set client which sends the request, default is [`Client::default`].
+    pub fn client(&mut self, client: &Client) -> &mut SignoutBuilder<'a> {
+        self.client = client.clone();
+        self
+    }
+
+    /// Get the server which answered the last request, see
+    /// [servers](Self::servers).
+    pub fn answered_server(&self) -> Option<&Url> {
+        self.answered.as_ref()
+    }
+
+    /// Build the request without sending it, for the first server to try.
+    /// See [http](crate::http).
+    pub fn http_request(&mut self) -> Result<HttpRequest> {
+        self.check()?;
+        self.http_request_to(&self.servers.candidates()[0])
+    }
+
     /// Make a request with the given parameters.
     /// If success, it will return `Ok(())`.
     pub async fn request(&mut self) -> Result<()> {
+        self.check()?;
+
+        let (server, response) = self
+            .servers
+            .send(&self.client, true, |server| self.http_request_to(server))
+            .await?;
+        self.answered = Some(server);
+
+        parse_response(&response)
+    }
+
+    fn check(&mut self) -> Result<()> {
         if self.params.username.is_none() {
             return Err(Error::MissingField("username"));
         }
@@ -93,16 +139,15 @@ impl<'a> SignoutBuilder<'a> {
             return Err(Error::MissingField("password"));
         }
 
-        let client = reqwest::Client::new();
-        let response = client
-            .post(self.server.join(self.endpoint)?)
-            .json(&self.params)
-            .send()
-            .await?;
+        Ok(())
+    }
 
-        match response.status() {
-            StatusCode::NO_CONTENT => Ok(()),
-            _ => Err(Error::from_response(response).await),
-        }
+    fn http_request_to(&self, server: &Url) -> Result<HttpRequest> {
+        HttpRequest::json(Method::POST, server.join(self.endpoint)?, &self.params)
     }
 }
+
+/// Read the response of a signout request, see [http](crate::http).
+pub fn parse_response(response: &HttpResponse) -> Result<()> {
+    response.no_content()
+}
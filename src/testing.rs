@@ -0,0 +1,706 @@
+//! Local server for integration tests
+//!
+//! [TestServer] emulates the authentication server, the session server and
+//! the profile and entitlements endpoints of the services API, on an
+//! ephemeral port of `127.0.0.1`. It answers like Mojang does, including
+//! its error messages, so that code using the crate can be tested offline.
+//! Other endpoints, such as `/player/certificates` of the services API,
+//! answer `404 Not Found`.
+//!
+//! It starts with these accounts, whose password is [SEEDED_PASSWORD]:
+//!
+//! | Username              | Profile | Owns the game |
+//! |-----------------------|---------|---------------|
+//! | `steve@example.com`   | `Steve` | yes           |
+//! | `alex@example.com`    | `Alex`  | yes           |
+//! | `new@example.com`     | none    | yes           |
+//! | `demo@example.com`    | none    | no            |
+
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_LENGTH};
+use reqwest::{Method, StatusCode, Url};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::consts::INVALID_CREDENTIALS_MESSAGE;
+
+/// Password of the accounts which a [TestServer] starts with.
+pub const SEEDED_PASSWORD: &str = "password";
+
+/// An account of a [TestServer]
+#[derive(Debug, Clone)]
+pub struct TestAccount {
+    pub id: Uuid,
+    pub username: String,
+    pub password: String,
+    pub profile: Option<TestProfile>,
+    pub owns_game: bool,
+}
+
+/// The Minecraft profile of a [TestAccount]
+#[derive(Debug, Clone)]
+pub struct TestProfile {
+    pub id: Uuid,
+    pub name: String,
+}
+
+/// `TestServer` is an in-process Yggdrasil, session and services server
+///
+/// Its url serves as the authentication, session and services server at
+/// once. The server stops when it is dropped.
+///
+/// For example:
+/// ```
+/// # use sage_auth::client::Client;
+/// # use sage_auth::pool::ServerPool;
+/// # use sage_auth::session::Session;
+/// # use sage_auth::testing::{TestServer, SEEDED_PASSWORD};
+/// # #[tokio::main(basic_scheduler)]
+/// # async fn main() {
+/// let server = TestServer::start().unwrap();
+/// let pool = ServerPool::single(server.url().clone());
+///
+/// let mut session =
+///     Session::login_with_pool(&Client::new(), &pool, "steve@example.com", SEEDED_PASSWORD)
+///         .await
+///         .unwrap();
+/// session.session_server(server.url().clone()).unwrap();
+/// session.join_server("SERVER_HASH").await.unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TestServer {
+    url: Url,
+    state: Arc<Mutex<State>>,
+    stopped: Arc<AtomicBool>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    accounts: Vec<TestAccount>,
+    tokens: HashMap<String, Token>,
+    joins: HashMap<String, TestProfile>,
+    latency: Duration,
+    status_overrides: HashMap<String, StatusCode>,
+    retry_after: Option<Duration>,
+    token_lifetime: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    client_token: String,
+    username: String,
+    issued_at: Instant,
+    expired: bool,
+}
+
+struct Request {
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+type Reply = (StatusCode, Option<Value>);
+
+impl TestAccount {
+    /// Create an account without profile, which does not own the game.
+    pub fn new<S, T>(username: S, password: T) -> TestAccount
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        TestAccount {
+            id: Uuid::new_v4(),
+            username: username.into(),
+            password: password.into(),
+            profile: None,
+            owns_game: false,
+        }
+    }
+
+    /// Give the account a profile named `name`, and the game.
+    pub fn profile<S: Into<String>>(&mut self, name: S) -> &mut TestAccount {
+        self.profile = Some(TestProfile {
+            id: Uuid::new_v4(),
+            name: name.into(),
+        });
+        self.owns_game = true;
+        self
+    }
+
+    /// Set whether the account owns the game.
+    pub fn owns_game(&mut self, owns_game: bool) -> &mut TestAccount {
+        self.owns_game = owns_game;
+        self
+    }
+}
+
+impl TestServer {
+    /// Start a server with the seeded accounts.
+    pub fn start() -> io::Result<TestServer> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = Url::parse(&format!("http://{}", listener.local_addr()?)).unwrap();
+
+        let mut state = State::default();
+        for (username, profile) in &[
+            ("steve@example.com", Some("Steve")),
+            ("alex@example.com", Some("Alex")),
+            ("new@example.com", None),
+        ] {
+            let mut account = TestAccount::new(*username, SEEDED_PASSWORD);
+            match profile {
+                Some(name) => account.profile(*name),
+                None => account.owns_game(true),
+            };
+            state.accounts.push(account);
+        }
+        state
+            .accounts
+            .push(TestAccount::new("demo@example.com", SEEDED_PASSWORD));
+
+        let server = TestServer {
+            url,
+            state: Arc::new(Mutex::new(state)),
+            stopped: Arc::new(AtomicBool::new(false)),
+        };
+
+        let state = server.state.clone();
+        let stopped = server.stopped.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let state = state.clone();
+                    thread::spawn(move || {
+                        let _ = serve(stream, &state);
+                    });
+                }
+            }
+        });
+
+        Ok(server)
+    }
+
+    /// Get the base url of the server.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Add an account, replacing any with the same username.
+    pub fn add_account(&self, account: &TestAccount) -> &TestServer {
+        let mut state = self.state.lock().unwrap();
+        state
+            .accounts
+            .retain(|existing| existing.username != account.username);
+        state.accounts.push(account.clone());
+        drop(state);
+        self
+    }
+
+    /// Get the account with `username`.
+    pub fn account(&self, username: &str) -> Option<TestAccount> {
+        self.state.lock().unwrap().account(username).cloned()
+    }
+
+    /// Delay every response by `latency`, default is none.
+    pub fn latency(&self, latency: Duration) -> &TestServer {
+        self.state.lock().unwrap().latency = latency;
+        self
+    }
+
+    /// Answer every request to `path` with `status`, or normally if `None`.
+    pub fn status_override(&self, path: &str, status: Option<StatusCode>) -> &TestServer {
+        let mut state = self.state.lock().unwrap();
+        match status {
+            Some(status) => state.status_overrides.insert(path.to_owned(), status),
+            None => state.status_overrides.remove(path),
+        };
+        drop(state);
+        self
+    }
+
+    /// Send `retry_after` as the `Retry-After` header of `429` and `503`
+    /// responses, default is none.
+    pub fn retry_after(&self, retry_after: Option<Duration>) -> &TestServer {
+        self.state.lock().unwrap().retry_after = retry_after;
+        self
+    }
+
+    /// Set how long access tokens are valid, default is forever. Expired
+    /// tokens can still be refreshed, like with Mojang.
+    pub fn token_lifetime(&self, token_lifetime: Option<Duration>) -> &TestServer {
+        self.state.lock().unwrap().token_lifetime = token_lifetime;
+        self
+    }
+
+    /// Expire the access tokens issued so far.
+    pub fn expire_tokens(&self) -> &TestServer {
+        for token in self.state.lock().unwrap().tokens.values_mut() {
+            token.expired = true;
+        }
+        self
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wake up the listener so that it sees the flag
+        if let Some(address) = self
+            .url
+            .socket_addrs(|| None)
+            .ok()
+            .and_then(|a| a.first().copied())
+        {
+            let _ = TcpStream::connect(address);
+        }
+    }
+}
+
+fn serve(stream: TcpStream, state: &Mutex<State>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let request = match read_request(&mut reader)? {
+        Some(request) => request,
+        None => return Ok(()),
+    };
+
+    let latency = state.lock().unwrap().latency;
+    thread::sleep(latency);
+
+    let mut state = state.lock().unwrap();
+    let (status, body) = state.handle(&request);
+    let retry_after = state.retry_after;
+    drop(state);
+
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or(""),
+        body.len()
+    );
+    if let Some(retry_after) = retry_after {
+        if status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE {
+            response.push_str(&format!("Retry-After: {}\r\n", retry_after.as_secs()));
+        }
+    }
+    if !body.is_empty() {
+        response.push_str("Content-Type: application/json\r\n");
+    }
+    response.push_str("\r\n");
+    response.push_str(&body);
+
+    let stream = reader.get_mut();
+    stream.write_all(response.as_bytes())?;
+    stream.flush()
+}
+
+fn read_request(reader: &mut BufReader<TcpStream>) -> io::Result<Option<Request>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or("").parse::<Method>();
+    let url = Url::parse("http://localhost")
+        .unwrap()
+        .join(parts.next().unwrap_or("/"));
+    let (method, url) = match (method, url) {
+        (Ok(method), Ok(url)) => (method, url),
+        _ => return Ok(None),
+    };
+
+    let mut headers = HeaderMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line == "\r\n" {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if let (Ok(name), Ok(value)) = (
+                name.trim().parse::<reqwest::header::HeaderName>(),
+                HeaderValue::from_str(value.trim()),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+    }
+
+    let length = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Some(Request {
+        method,
+        url,
+        headers,
+        body,
+    }))
+}
+
+fn error(status: StatusCode, error: &str, message: &str) -> Reply {
+    (
+        status,
+        Some(json!({ "error": error, "errorMessage": message })),
+    )
+}
+
+fn invalid_credentials() -> Reply {
+    error(
+        StatusCode::FORBIDDEN,
+        "ForbiddenOperationException",
+        INVALID_CREDENTIALS_MESSAGE,
+    )
+}
+
+fn invalid_token() -> Reply {
+    error(
+        StatusCode::FORBIDDEN,
+        "ForbiddenOperationException",
+        "Invalid token.",
+    )
+}
+
+fn profile_json(profile: &TestProfile) -> Value {
+    json!({ "id": profile.id.to_simple().to_string(), "name": profile.name })
+}
+
+fn str_field<'a>(body: &'a Value, name: &str) -> Option<&'a str> {
+    body.get(name).and_then(Value::as_str)
+}
+
+impl State {
+    fn account(&self, username: &str) -> Option<&TestAccount> {
+        self.accounts
+            .iter()
+            .find(|account| account.username == username)
+    }
+
+    fn handle(&mut self, request: &Request) -> Reply {
+        let path = request.url.path();
+        if let Some(status) = self.status_overrides.get(path) {
+            let reason = status.canonical_reason().unwrap_or("Error");
+            return error(*status, reason, "Injected by the test server");
+        }
+
+        if path.starts_with("/session/minecraft/profile/") && request.method == Method::GET {
+            return self.profile(&path["/session/minecraft/profile/".len()..]);
+        }
+        if request.method == Method::GET {
+            return match path {
+                "/session/minecraft/hasJoined" => self.has_joined(&request.url),
+                "/minecraft/profile" => self.minecraft_profile(request),
+                "/entitlements/mcstore" | "/entitlements/license" => self.entitlements(request),
+                _ => not_found(),
+            };
+        }
+        let known = matches!(
+            path,
+            "/authenticate"
+                | "/refresh"
+                | "/validate"
+                | "/invalidate"
+                | "/signout"
+                | "/session/minecraft/join"
+        );
+        if !known {
+            return not_found();
+        }
+        if request.method != Method::POST {
+            return error(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "Method Not Allowed",
+                "A request was made of a resource using a request method not supported by that resource",
+            );
+        }
+
+        let body: Value = match serde_json::from_slice(&request.body) {
+            Ok(body) => body,
+            Err(_) => {
+                return error(
+                    StatusCode::BAD_REQUEST,
+                    "IllegalArgumentException",
+                    "Invalid request body.",
+                )
+            }
+        };
+        match path {
+            "/authenticate" => self.authenticate(&body),
+            "/refresh" => self.refresh(&body),
+            "/validate" => self.validate(&body),
+            "/invalidate" => self.invalidate(&body),
+            "/signout" => self.signout(&body),
+            "/session/minecraft/join" => self.join(&body),
+            _ => not_found(),
+        }
+    }
+
+    fn issue_token(&mut self, username: &str, client_token: String) -> String {
+        let access_token = Uuid::new_v4().to_simple().to_string();
+        self.tokens.insert(
+            access_token.clone(),
+            Token {
+                client_token,
+                username: username.to_owned(),
+                issued_at: Instant::now(),
+                expired: false,
+            },
+        );
+        access_token
+    }
+
+    /// Get the token if it exists and matches `client_token`, when given.
+    fn token(&self, access_token: Option<&str>, client_token: Option<&str>) -> Option<&Token> {
+        let token = self.tokens.get(access_token?)?;
+        match client_token {
+            Some(client_token) if client_token != token.client_token => None,
+            _ => Some(token),
+        }
+    }
+
+    fn is_usable(&self, token: &Token) -> bool {
+        !token.expired
+            && self
+                .token_lifetime
+                .is_none_or(|lifetime| token.issued_at.elapsed() < lifetime)
+    }
+
+    fn credentials(&self, body: &Value) -> Option<TestAccount> {
+        let account = self.account(str_field(body, "username")?)?;
+        if Some(account.password.as_str()) == str_field(body, "password") {
+            Some(account.clone())
+        } else {
+            None
+        }
+    }
+
+    fn authenticate(&mut self, body: &Value) -> Reply {
+        let account = match self.credentials(body) {
+            Some(account) => account,
+            None => return invalid_credentials(),
+        };
+        let client_token = str_field(body, "clientToken")
+            .map(str::to_owned)
+            .unwrap_or_else(|| Uuid::new_v4().to_simple().to_string());
+        let access_token = self.issue_token(&account.username, client_token.clone());
+
+        let mut reply = json!({
+            "accessToken": access_token,
+            "clientToken": client_token,
+            "availableProfiles": account.profile.iter().map(profile_json).collect::<Vec<_>>(),
+        });
+        if let Some(profile) = &account.profile {
+            reply["selectedProfile"] = profile_json(profile);
+        }
+        if body.get("requestUser").and_then(Value::as_bool) == Some(true) {
+            reply["user"] = user_json(&account);
+        }
+        (StatusCode::OK, Some(reply))
+    }
+
+    fn refresh(&mut self, body: &Value) -> Reply {
+        let access_token = str_field(body, "accessToken");
+        let token = match self.token(access_token, str_field(body, "clientToken")) {
+            Some(token) => token.clone(),
+            None => return invalid_token(),
+        };
+        let account = match self.account(&token.username) {
+            Some(account) => account.clone(),
+            None => return invalid_token(),
+        };
+
+        self.tokens.remove(access_token.unwrap());
+        let access_token = self.issue_token(&account.username, token.client_token.clone());
+        let mut reply = json!({
+            "accessToken": access_token,
+            "clientToken": token.client_token,
+        });
+        if let Some(profile) = &account.profile {
+            reply["selectedProfile"] = profile_json(profile);
+        }
+        if body.get("requestUser").and_then(Value::as_bool) == Some(true) {
+            reply["user"] = user_json(&account);
+        }
+        (StatusCode::OK, Some(reply))
+    }
+
+    fn validate(&self, body: &Value) -> Reply {
+        let token = self.token(
+            str_field(body, "accessToken"),
+            str_field(body, "clientToken"),
+        );
+        match token {
+            Some(token) if self.is_usable(token) => (StatusCode::NO_CONTENT, None),
+            _ => invalid_token(),
+        }
+    }
+
+    fn invalidate(&mut self, body: &Value) -> Reply {
+        let access_token = str_field(body, "accessToken");
+        if self
+            .token(access_token, str_field(body, "clientToken"))
+            .is_some()
+        {
+            self.tokens.remove(access_token.unwrap());
+        }
+        (StatusCode::NO_CONTENT, None)
+    }
+
+    fn signout(&mut self, body: &Value) -> Reply {
+        match self.credentials(body) {
+            Some(account) => {
+                self.tokens
+                    .retain(|_, token| token.username != account.username);
+                (StatusCode::NO_CONTENT, None)
+            }
+            None => invalid_credentials(),
+        }
+    }
+
+    /// Get the profile of a usable `access_token`.
+    fn token_profile(&self, access_token: Option<&str>) -> Option<Option<TestProfile>> {
+        let token = self.token(access_token, None)?;
+        if !self.is_usable(token) {
+            return None;
+        }
+        Some(self.account(&token.username)?.profile.clone())
+    }
+
+    fn join(&mut self, body: &Value) -> Reply {
+        let profile = match self.token_profile(str_field(body, "accessToken")) {
+            Some(Some(profile)) => profile,
+            _ => return invalid_token(),
+        };
+        let selected = str_field(body, "selectedProfile").and_then(|id| Uuid::parse_str(id).ok());
+        if selected != Some(profile.id) {
+            return error(
+                StatusCode::FORBIDDEN,
+                "ForbiddenOperationException",
+                "Invalid profile.",
+            );
+        }
+
+        let server_id = str_field(body, "serverId").unwrap_or("").to_owned();
+        self.joins.insert(server_id, profile);
+        (StatusCode::NO_CONTENT, None)
+    }
+
+    fn has_joined(&self, url: &Url) -> Reply {
+        let query: HashMap<_, _> = url.query_pairs().collect();
+        let joined = query
+            .get("serverId")
+            .and_then(|server_id| self.joins.get(server_id.as_ref()));
+        match (joined, query.get("username")) {
+            (Some(profile), Some(username)) if profile.name == *username => {
+                let mut reply = profile_json(profile);
+                reply["properties"] = json!([]);
+                (StatusCode::OK, Some(reply))
+            }
+            _ => (StatusCode::NO_CONTENT, None),
+        }
+    }
+
+    fn profile(&self, id: &str) -> Reply {
+        let id = Uuid::parse_str(id).ok();
+        let profile = self
+            .accounts
+            .iter()
+            .filter_map(|account| account.profile.as_ref())
+            .find(|profile| Some(profile.id) == id);
+        match profile {
+            Some(profile) => {
+                let mut reply = profile_json(profile);
+                reply["properties"] = json!([]);
+                (StatusCode::OK, Some(reply))
+            }
+            None => (StatusCode::NO_CONTENT, None),
+        }
+    }
+
+    /// Get the account authorized by the bearer token of `request`.
+    fn bearer_account(&self, request: &Request) -> Option<&TestAccount> {
+        let access_token = request
+            .headers
+            .get(AUTHORIZATION)?
+            .to_str()
+            .ok()?
+            .strip_prefix("Bearer ")?;
+        let token = self.token(Some(access_token), None)?;
+        if !self.is_usable(token) {
+            return None;
+        }
+        self.account(&token.username)
+    }
+
+    fn minecraft_profile(&self, request: &Request) -> Reply {
+        let account = match self.bearer_account(request) {
+            Some(account) => account,
+            None => return (StatusCode::UNAUTHORIZED, None),
+        };
+        match &account.profile {
+            Some(profile) => {
+                let mut reply = profile_json(profile);
+                reply["skins"] = json!([]);
+                reply["capes"] = json!([]);
+                (StatusCode::OK, Some(reply))
+            }
+            None => (
+                StatusCode::NOT_FOUND,
+                Some(json!({
+                    "path": "/minecraft/profile",
+                    "errorType": "NOT_FOUND",
+                    "error": "NOT_FOUND",
+                    "errorMessage": "The server has not found anything matching the request URI",
+                })),
+            ),
+        }
+    }
+
+    fn entitlements(&self, request: &Request) -> Reply {
+        let account = match self.bearer_account(request) {
+            Some(account) => account,
+            None => return (StatusCode::UNAUTHORIZED, None),
+        };
+        let items = if account.owns_game {
+            json!([
+                { "name": "product_minecraft", "signature": "" },
+                { "name": "game_minecraft", "signature": "" },
+            ])
+        } else {
+            json!([])
+        };
+        (
+            StatusCode::OK,
+            Some(json!({ "items": items, "signature": "", "keyId": "1" })),
+        )
+    }
+}
+
+fn user_json(account: &TestAccount) -> Value {
+    json!({
+        "id": account.id.to_simple().to_string(),
+        "username": account.username,
+        "properties": [],
+    })
+}
+
+fn not_found() -> Reply {
+    error(
+        StatusCode::NOT_FOUND,
+        "Not Found",
+        "The server has not found anything matching the request URI",
+    )
+}
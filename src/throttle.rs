@@ -0,0 +1,239 @@
+//! Brute-force protection for self-hosted authentication servers
+//!
+//! Requires the `server` feature.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::consts::{INVALID_CREDENTIALS_MESSAGE, TOO_MANY_ATTEMPTS_MESSAGE};
+use crate::password::PasswordVerifier;
+use crate::{ApiError, Error, Result};
+
+/// Limits of a [`Throttle`].
+#[derive(Clone, Debug)]
+pub struct ThrottleConfig {
+    /// Failed attempts allowed for one account before it is locked.
+    pub max_account_failures: u32,
+
+    /// Failed attempts allowed from one IP address before it is locked.
+    pub max_ip_failures: u32,
+
+    /// Length of the first lockout, doubled by every further failure.
+    pub base_lockout: Duration,
+
+    /// Upper bound of a lockout.
+    pub max_lockout: Duration,
+
+    /// Counters are forgotten after this long without failures.
+    pub window: Duration,
+}
+
+/// Per-account and per-IP failed login counters with exponential lockout.
+///
+/// Rejections look exactly like Mojang's: a locked out attempt fails with
+/// `ForbiddenOperationException` "Invalid credentials." without checking the
+/// password, and a wrong password or unknown account fails with "Invalid
+/// credentials. Invalid username or password.".
+///
+/// For example:
+/// ```no_run
+/// # use sage_auth::password::Argon2Verifier;
+/// # use sage_auth::throttle::Throttle;
+/// # use sage_auth::error::Result;
+/// # fn anonymous(stored_hash: Option<&str>) -> Result<()> {
+/// let throttle = Throttle::default();
+/// let verifier = Argon2Verifier::new();
+/// let ip = "127.0.0.1".parse().unwrap();
+///
+/// throttle.authenticate("user@example.com", ip, "PASSWORD", stored_hash, &verifier)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Throttle {
+    config: ThrottleConfig,
+    accounts: Mutex<HashMap<String, Counter>>,
+    ips: Mutex<HashMap<IpAddr, Counter>>,
+}
+
+struct Counter {
+    failures: u32,
+    last_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> ThrottleConfig {
+        ThrottleConfig {
+            max_account_failures: 5,
+            max_ip_failures: 20,
+            base_lockout: Duration::from_secs(30),
+            max_lockout: Duration::from_secs(60 * 60),
+            window: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+impl Default for Throttle {
+    fn default() -> Throttle {
+        Throttle::new(ThrottleConfig::default())
+    }
+}
+
+impl Counter {
+    fn is_locked(&self, now: Instant) -> bool {
+        self.locked_until.is_some_and(|until| now < until)
+    }
+
+    fn is_stale(&self, now: Instant, window: Duration) -> bool {
+        !self.is_locked(now) && now.duration_since(self.last_failure) >= window
+    }
+}
+
+impl Throttle {
+    pub fn new(config: ThrottleConfig) -> Throttle {
+        Throttle {
+            config,
+            accounts: Mutex::new(HashMap::new()),
+            ips: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Check whether `account` may attempt to log in from `ip`.
+    ///
+    /// Returns the "Invalid credentials." error if either is locked out.
+    pub fn check(&self, account: &str, ip: IpAddr) -> Result<()> {
+        let now = Instant::now();
+        let account_locked = is_locked(&self.accounts, &normalize(account), now);
+        let ip_locked = is_locked(&self.ips, &ip, now);
+
+        if account_locked || ip_locked {
+            Err(forbidden(TOO_MANY_ATTEMPTS_MESSAGE))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Count a failed attempt for `account` and `ip`.
+    pub fn record_failure(&self, account: &str, ip: IpAddr) {
+        let now = Instant::now();
+        let config = &self.config;
+        record_failure(
+            &self.accounts,
+            normalize(account),
+            config.max_account_failures,
+            config,
+            now,
+        );
+        record_failure(&self.ips, ip, config.max_ip_failures, config, now);
+    }
+
+    /// Forget the failures of `account` after a successful login.
+    ///
+    /// The counter of `ip` is kept, so a valid login does not hide
+    /// guessing against other accounts from the same address.
+    pub fn record_success(&self, account: &str) {
+        self.accounts.lock().unwrap().remove(&normalize(account));
+    }
+
+    /// Check the throttle, verify `password` and record the outcome.
+    ///
+    /// `stored_hash` is `None` for unknown accounts. They are rejected with
+    /// the same error as a wrong password, after spending the time a
+    /// verification would take.
+    pub fn authenticate<V: PasswordVerifier + ?Sized>(
+        &self,
+        account: &str,
+        ip: IpAddr,
+        password: &str,
+        stored_hash: Option<&str>,
+        verifier: &V,
+    ) -> Result<()> {
+        self.check(account, ip)?;
+
+        let valid = match stored_hash {
+            Some(hash) => verifier.verify(password, hash),
+            None => {
+                let _ = verifier.hash(password);
+                false
+            }
+        };
+
+        if valid {
+            self.record_success(account);
+            Ok(())
+        } else {
+            self.record_failure(account, ip);
+            Err(forbidden(INVALID_CREDENTIALS_MESSAGE))
+        }
+    }
+
+    /// Drop counters which are neither locked nor recent.
+    ///
+    /// Call it periodically to bound memory usage.
+    pub fn prune(&self) {
+        let now = Instant::now();
+        let window = self.config.window;
+        self.accounts
+            .lock()
+            .unwrap()
+            .retain(|_, counter| !counter.is_stale(now, window));
+        self.ips
+            .lock()
+            .unwrap()
+            .retain(|_, counter| !counter.is_stale(now, window));
+    }
+}
+
+fn is_locked<K: Eq + Hash>(counters: &Mutex<HashMap<K, Counter>>, key: &K, now: Instant) -> bool {
+    counters
+        .lock()
+        .unwrap()
+        .get(key)
+        .is_some_and(|counter| counter.is_locked(now))
+}
+
+fn record_failure<K: Eq + Hash>(
+    counters: &Mutex<HashMap<K, Counter>>,
+    key: K,
+    max_failures: u32,
+    config: &ThrottleConfig,
+    now: Instant,
+) {
+    let mut counters = counters.lock().unwrap();
+    let counter = counters.entry(key).or_insert(Counter {
+        failures: 0,
+        last_failure: now,
+        locked_until: None,
+    });
+
+    if counter.is_stale(now, config.window) {
+        counter.failures = 0;
+    }
+    counter.failures += 1;
+    counter.last_failure = now;
+
+    if counter.failures >= max_failures {
+        let exponent = (counter.failures - max_failures).min(31);
+        let lockout = config
+            .base_lockout
+            .checked_mul(1 << exponent)
+            .map_or(config.max_lockout, |lockout| {
+                lockout.min(config.max_lockout)
+            });
+        counter.locked_until = Some(now + lockout);
+    }
+}
+
+fn normalize(account: &str) -> String {
+    account.trim().to_lowercase()
+}
+
+fn forbidden(message: &str) -> Error {
+    Error::API(
+        ApiError::ForbiddenOperationException(message.to_owned()),
+        None,
+    )
+}
@@ -0,0 +1,216 @@
+//! HTTP transports
+//!
+//! A [Client](crate::client::Client) sends its requests through a
+//! [Transport], which is reqwest by default. [MockTransport] answers them
+//! in process instead, to test code which uses the crate without network.
+
+use reqwest::header::{HeaderValue, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::http::{HttpRequest, HttpResponse};
+use crate::{Error, Result};
+
+/// Future of [`Transport::send`]
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<HttpResponse>> + Send + 'a>>;
+
+/// `Transport` exchanges a request for its response
+///
+/// Retries and rate limits are applied by the client, a transport sends
+/// each request once. Transports which cannot connect or time out report
+/// [`Error::Connect`] and [`Error::Timeout`], so that the request is
+/// retried.
+pub trait Transport: fmt::Debug + Send + Sync {
+    /// Send `request` and read the whole response.
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+/// Transport over a reqwest client
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    http: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(http: reqwest::Client) -> ReqwestTransport {
+        ReqwestTransport { http }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let request = request.into_reqwest(&self.http).build()?;
+            let response = self.http.execute(request).await?;
+            HttpResponse::read(response).await
+        })
+    }
+}
+
+/// A scripted answer of [MockTransport]
+#[derive(Debug, Clone)]
+pub enum MockResponse {
+    Response(HttpResponse),
+
+    /// Fail with [`Error::Timeout`].
+    Timeout,
+
+    /// Fail with [`Error::Connect`].
+    ConnectionRefused,
+}
+
+impl MockResponse {
+    /// Answer with `status` and an empty body.
+    pub fn status(status: StatusCode) -> MockResponse {
+        MockResponse::Response(HttpResponse::new(status, vec![]))
+    }
+
+    /// Answer with `status` and `body` as JSON.
+    pub fn json<T: Serialize>(status: StatusCode, body: &T) -> MockResponse {
+        let body = serde_json::to_vec(body).expect("mock response is not serializable");
+        MockResponse::Response(HttpResponse::new(status, body))
+    }
+
+    /// Answer with `status` and the raw `body`, such as malformed JSON.
+    pub fn body<B: Into<Vec<u8>>>(status: StatusCode, body: B) -> MockResponse {
+        MockResponse::Response(HttpResponse::new(status, body.into()))
+    }
+
+    /// Answer with `429 Too Many Requests`, asking to retry after
+    /// `retry_after`.
+    pub fn too_many_requests(retry_after: Duration) -> MockResponse {
+        let mut response = HttpResponse::new(StatusCode::TOO_MANY_REQUESTS, vec![]);
+        response
+            .headers
+            .insert(RETRY_AFTER, HeaderValue::from(retry_after.as_secs()));
+        MockResponse::Response(response)
+    }
+}
+
+/// `MockTransport` answers requests with scripted responses
+///
+/// Requests are matched by method and path, regardless of the server.
+/// The responses of a route are answered in order, and the last one is
+/// repeated. Requests without a route are answered `404 Not Found`.
+/// Clones share the routes and the recorded requests.
+///
+/// For example:
+/// ```
+/// # use reqwest::{Method, StatusCode};
+/// # use sage_auth::client::Client;
+/// # use sage_auth::transport::{MockResponse, MockTransport};
+/// # use sage_auth::validate::ValidateBuilder;
+/// # use uuid::Uuid;
+/// # #[tokio::main(basic_scheduler)]
+/// # async fn main() {
+/// let mut mock = MockTransport::new();
+/// mock.on(
+///     Method::POST,
+///     "/validate",
+///     MockResponse::status(StatusCode::NO_CONTENT),
+/// );
+///
+/// ValidateBuilder::new()
+///     .client(&Client::with_transport(mock.clone()))
+///     .access_token("ACCESS_TOKEN")
+///     .client_token(Uuid::new_v4())
+///     .request()
+///     .await
+///     .unwrap();
+/// assert_eq!(mock.requests().len(), 1);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    routes: Vec<Route>,
+    requests: Vec<HttpRequest>,
+}
+
+#[derive(Debug)]
+struct Route {
+    method: Method,
+    path: String,
+    responses: VecDeque<MockResponse>,
+}
+
+impl MockTransport {
+    pub fn new() -> MockTransport {
+        MockTransport::default()
+    }
+
+    /// Answer the next request with `method` to `path` with `response`.
+    pub fn on(&mut self, method: Method, path: &str, response: MockResponse) -> &mut MockTransport {
+        let mut state = self.state.lock().unwrap();
+        match state
+            .routes
+            .iter_mut()
+            .find(|route| route.method == method && route.path == path)
+        {
+            Some(route) => route.responses.push_back(response),
+            None => state.routes.push(Route {
+                method,
+                path: path.to_owned(),
+                responses: vec![response].into(),
+            }),
+        }
+        drop(state);
+        self
+    }
+
+    /// Get the requests received so far, in order.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Forget the requests received so far.
+    pub fn clear_requests(&self) {
+        self.state.lock().unwrap().requests.clear();
+    }
+
+    fn answer(&self, request: HttpRequest) -> MockResponse {
+        let mut state = self.state.lock().unwrap();
+        let method = request.method.clone();
+        let path = request.url.path().to_owned();
+        state.requests.push(request);
+
+        let route = state
+            .routes
+            .iter_mut()
+            .find(|route| route.method == method && route.path == path);
+        match route {
+            Some(route) if route.responses.len() > 1 => route.responses.pop_front().unwrap(),
+            Some(route) => route.responses[0].clone(),
+            None => MockResponse::body(
+                StatusCode::NOT_FOUND,
+                format!("no mock response for {} {}", method, path),
+            ),
+        }
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let url = request.url.clone();
+        let response = self.answer(request);
+        Box::pin(async move {
+            match response {
+                MockResponse::Response(response) => Ok(response),
+                MockResponse::Timeout => Err(Error::Timeout),
+                MockResponse::ConnectionRefused => {
+                    Err(Error::Connect(format!("connection refused by {}", url)))
+                }
+            }
+        })
+    }
+}
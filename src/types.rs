@@ -1,8 +1,7 @@
 //! Common types and conversion functions.
 
-use serde::{de::Deserializer, ser::Serializer};
-use serde_derive::Deserialize;
-use std::collections::HashMap;
+use serde::ser::Serializer;
+use serde_derive::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// Mojang account information
@@ -15,7 +14,9 @@ pub struct User {
     /// User identifier.
     pub id: Uuid,
 
-    /// Username, format is `user@example.com`.
+    /// Username, format is `user@example.com`. Empty if the server leaves
+    /// it out, as authlib-injector servers do.
+    #[serde(default)]
     pub username: String,
 }
 
@@ -43,6 +44,28 @@ pub struct Profile {
     /// Only appears in the response if `true`. Default to `false`. Redundant to the newer legacyProfile.
     #[serde(default)]
     pub legacy: bool,
+
+    /// Profile properties, such as `textures`. Only present in responses
+    /// from the session server.
+    #[serde(default)]
+    pub properties: Vec<Property>,
+}
+
+/// Profile property
+///
+/// The session server signs the properties of a profile when asked with
+/// `unsigned=false`, see [signing](crate::signing).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Property {
+    pub name: String,
+
+    /// Base64 encoded value, such as the textures JSON.
+    pub value: String,
+
+    /// Base64 encoded `SHA1withRSA` signature of `value`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
 }
 
 /// Mojang API error response
@@ -53,69 +76,13 @@ pub struct ErrorMessage {
     pub error: String,
 
     /// Longer description which can be shown to the user.
+    /// Some services API errors leave it out.
+    #[serde(default)]
     pub error_message: String,
 
     pub cause: Option<String>,
 }
 
-/// Convert Mojang special key-value format to [HashMap]
-///
-/// Mojang account properties are given in the following format:
-///
-/// ```json
-/// [
-///     {
-///         "name": "preferredLanguage",
-///         "value": "en"
-///     },
-///     {
-///         "name": "twitch_access_token",
-///         "value": "twitch oauth token"
-///     }
-/// ]
-/// ```
-///
-/// The function is used to convert this format into [HashMap].
-fn properties_parser<'de, D>(deserializer: D) -> Result<HashMap<String, String>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    use serde::de::{SeqAccess, Visitor};
-    use std::fmt;
-
-    struct PropertiesParser;
-
-    #[derive(Deserialize)]
-    #[serde(rename_all = "camelCase")]
-    struct Property {
-        name: String,
-        value: String,
-    }
-
-    impl<'de> Visitor<'de> for PropertiesParser {
-        type Value = HashMap<String, String>;
-
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("mojang properties, which separate name and value into two fields")
-        }
-
-        fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
-        where
-            S: SeqAccess<'de>,
-        {
-            let mut map = HashMap::new();
-
-            while let Some(property) = seq.next_element::<Property>()? {
-                map.insert(property.name, property.value);
-            }
-
-            Ok(map)
-        }
-    }
-
-    deserializer.deserialize_seq(PropertiesParser)
-}
-
 /// Serialize Uuid to string without hyphens
 pub(crate) fn serialize_uuid_simple<S>(uuid: &Uuid, serializer: S) -> Result<S::Ok, S::Error>
 where
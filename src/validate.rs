@@ -1,10 +1,13 @@
 //! Validate request
 
-use reqwest::{IntoUrl, StatusCode, Url};
+use reqwest::{IntoUrl, Method, Url};
 use serde_derive::Serialize;
 use uuid::Uuid;
 
+use crate::client::{Client, DEFAULT_CLIENT};
 use crate::consts::DEFAULT_SERVER;
+use crate::http::{HttpRequest, HttpResponse};
+use crate::pool::ServerPool;
 use crate::{Error, Result};
 
 #[derive(Serialize)]
@@ -35,10 +38,13 @@ struct ValidateParams<'a> {
 /// ```
 pub struct ValidateBuilder<'a> {
     params: ValidateParams<'a>,
-    server: Url,
+    servers: ServerPool,
     endpoint: &'a str,
+    client: Client,
+    answered: Option<Url>,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for ValidateParams<'_> {
     fn default() -> ValidateParams<'static> {
         ValidateParams {
@@ -52,8 +58,10 @@ impl Default for ValidateBuilder<'_> {
     fn default() -> ValidateBuilder<'static> {
         ValidateBuilder {
             params: ValidateParams::default(),
-            server: (*DEFAULT_SERVER).clone(),
+            servers: ServerPool::single((*DEFAULT_SERVER).clone()),
             endpoint: "/validate",
+            client: DEFAULT_CLIENT.clone(),
+            answered: None,
         }
     }
 }
@@ -77,19 +85,57 @@ impl<'a> ValidateBuilder<'a> {
 
     /// Set base url, default is `https://authserver.mojang.com`.
     pub fn server<T: IntoUrl>(&mut self, server: T) -> Result<&mut ValidateBuilder<'a>> {
-        self.server = server.into_url()?;
+        self.servers = ServerPool::single(server.into_url()?);
         Ok(self)
     }
 
+    /// Set mirrored servers to fail over between, instead of a single
+    /// [server](Self::server).
+    pub fn servers(&mut self, servers: &ServerPool) -> &mut ValidateBuilder<'a> {
+        self.servers = servers.clone();
+        self
+    }
+
     /// set endpoint, default is `/authenticate`.
     pub fn endpoint(&mut self, endpoint: &'a str) -> &mut ValidateBuilder<'a> {
         self.endpoint = endpoint;
         self
     }
 
+    /// Set client which sends the request, default is [`Client::default`].
+    pub fn client(&mut self, client: &Client) -> &mut ValidateBuilder<'a> {
+        self.client = client.clone();
+        self
+    }
+
+    /// Get the server which answered the last request, see
+    /// [servers](Self::servers).
+    pub fn answered_server(&self) -> Option<&Url> {
+        self.answered.as_ref()
+    }
+
+    /// Build the request without sending it, for the first server to try.
+    /// See [http](crate::http).
+    pub fn http_request(&mut self) -> Result<HttpRequest> {
+        self.check()?;
+        self.http_request_to(&self.servers.candidates()[0])
+    }
+
     /// Make a request with the given parameters.
     /// If success, it will return `Ok(())`.
     pub async fn request(&mut self) -> Result<()> {
+        self.check()?;
+
+        let (server, response) = self
+            .servers
+            .send(&self.client, true, |server| self.http_request_to(server))
+            .await?;
+        self.answered = Some(server);
+
+        parse_response(&response)
+    }
+
+    fn check(&mut self) -> Result<()> {
         if self.params.access_token.is_none() {
             return Err(Error::MissingField("access_token"));
         }
@@ -97,16 +143,15 @@ impl<'a> ValidateBuilder<'a> {
             return Err(Error::MissingField("client_token"));
         }
 
-        let client = reqwest::Client::new();
-        let response = client
-            .post(self.server.join(self.endpoint)?)
-            .json(&self.params)
-            .send()
-            .await?;
+        Ok(())
+    }
 
-        match response.status() {
-            StatusCode::NO_CONTENT => Ok(()),
-            _ => Err(Error::from_response(response).await),
-        }
+    fn http_request_to(&self, server: &Url) -> Result<HttpRequest> {
+        HttpRequest::json(Method::POST, server.join(self.endpoint)?, &self.params)
     }
 }
+
+/// Read the response of a validate request, see [http](crate::http).
+pub fn parse_response(response: &HttpResponse) -> Result<()> {
+    response.no_content()
+}
@@ -0,0 +1,29 @@
+use sage_auth::blocked_servers::{candidates, BlockedServers};
+
+#[test]
+fn test_candidates() {
+    assert_eq!(
+        candidates("Play.Example.com"),
+        vec!["play.example.com", "*.example.com", "*.com"]
+    );
+    assert_eq!(
+        candidates("10.0.0.1"),
+        vec!["10.0.0.1", "10.0.0.*", "10.0.*", "10.*"]
+    );
+    assert_eq!(candidates("localhost"), vec!["localhost"]);
+}
+
+#[test]
+fn test_is_blocked() {
+    // SHA-1 of `*.example.com` and `10.0.0.*`
+    let list = BlockedServers::parse(
+        "8c7122d652cb7be22d1986f1f30b07fd5108d9c0\naa9198662aa821b38228ff65342b7f06a40e7747\n",
+    );
+    assert_eq!(list.len(), 2);
+
+    assert!(list.is_blocked("mc.example.com"));
+    assert!(list.is_blocked("A.B.EXAMPLE.COM."));
+    assert!(list.is_blocked("10.0.0.42"));
+    assert!(!list.is_blocked("example.com"));
+    assert!(!list.is_blocked("10.0.1.1"));
+}
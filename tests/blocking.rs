@@ -0,0 +1,117 @@
+#![cfg(feature = "blocking")]
+
+use reqwest::{Method, StatusCode};
+use sage_auth::blocking::{RealmsClient, Session, ValidateBuilder};
+use sage_auth::client::{Client, RetryPolicy};
+use sage_auth::pool::ServerPool;
+use sage_auth::session::AccountState;
+use sage_auth::testing::{TestServer, SEEDED_PASSWORD};
+use sage_auth::transport::{MockResponse, MockTransport};
+use sage_auth::{ApiError, Error};
+use uuid::Uuid;
+
+fn login(server: &TestServer, username: &str) -> Session {
+    let mut client = Client::new();
+    client.retry_policy(RetryPolicy::none());
+    let pool = ServerPool::single(server.url().clone());
+
+    let mut session = Session::login_with_pool(&client, &pool, username, SEEDED_PASSWORD).unwrap();
+    session
+        .session_server(server.url().clone())
+        .unwrap()
+        .services_server(server.url().clone())
+        .unwrap();
+    session
+}
+
+#[test]
+fn test_validate() {
+    let server = TestServer::start().unwrap();
+    let session = login(&server, "steve@example.com");
+
+    let mut builder = ValidateBuilder::new();
+    builder
+        .server(server.url().clone())
+        .unwrap()
+        .access_token(session.access_token())
+        .client_token(session.client_token());
+    builder.request().unwrap();
+
+    server.expire_tokens();
+    match builder.request() {
+        Err(Error::API(ApiError::ForbiddenOperationException(message), _)) => {
+            assert_eq!(message, "Invalid token.")
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_http_request() {
+    let request = ValidateBuilder::new()
+        .server("https://auth.example.com/")
+        .unwrap()
+        .access_token("ACCESS_TOKEN")
+        .client_token(Uuid::nil())
+        .http_request()
+        .unwrap();
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(request.url.as_str(), "https://auth.example.com/validate");
+
+    match ValidateBuilder::new().http_request() {
+        Err(Error::MissingField("access_token")) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_session() {
+    let server = TestServer::start().unwrap();
+    let mut session = login(&server, "steve@example.com");
+    assert_eq!(session.profile().unwrap().name, "Steve");
+    session.validate().unwrap();
+
+    let access_token = session.access_token().to_owned();
+    session.refresh().unwrap();
+    assert_ne!(session.access_token(), access_token);
+
+    let session = login(&server, "new@example.com");
+    assert!(session.profile().is_none());
+    session.validate().unwrap();
+}
+
+#[test]
+fn test_services() {
+    let server = TestServer::start().unwrap();
+    let session = login(&server, "steve@example.com");
+
+    assert_eq!(session.account_state().unwrap(), AccountState::Owned);
+    let services = session.services();
+    assert_eq!(services.profile().unwrap().name, "Steve");
+    assert!(services.entitlements().unwrap().owns_minecraft());
+
+    let session = login(&server, "demo@example.com");
+    assert_eq!(session.account_state().unwrap(), AccountState::Demo);
+}
+
+#[test]
+fn test_realms() {
+    let mut mock = MockTransport::new();
+    mock.on(
+        Method::GET,
+        "/mco/available",
+        MockResponse::body(StatusCode::OK, "true"),
+    )
+    .on(
+        Method::GET,
+        "/invites/count/pending",
+        MockResponse::body(StatusCode::OK, "2"),
+    );
+    let mut client = Client::with_transport(mock);
+    client.retry_policy(RetryPolicy::none());
+
+    let mut realms = RealmsClient::new("ACCESS_TOKEN", Uuid::nil(), "Steve", "1.20.4");
+    realms.client(&client);
+    assert!(realms.is_available().unwrap());
+    assert_eq!(realms.pending_invites_count().unwrap(), 2);
+}
@@ -0,0 +1,156 @@
+//! These tests record and replay the local test server. Cassettes of
+//! Mojang, LittleSkin and Ely.by are pending: record them with
+//! `test_record` and replay them here.
+
+use reqwest::StatusCode;
+use sage_auth::auth::{self, AuthenticateBuilder};
+use sage_auth::cassette::{Cassette, RecordingTransport, ReplayTransport, REDACTED};
+use sage_auth::client::{Client, RetryPolicy};
+use sage_auth::refresh::RefreshBuilder;
+use sage_auth::testing::{TestAccount, TestServer, SEEDED_PASSWORD};
+use sage_auth::transport::ReqwestTransport;
+use sage_auth::{ApiError, Error};
+use std::env;
+
+#[tokio::test]
+async fn test_replay() {
+    let server = TestServer::start().unwrap();
+    let recorder = RecordingTransport::new(ReqwestTransport::default());
+    let client = Client::with_transport(recorder.clone());
+    for password in &[SEEDED_PASSWORD, "WRONG"] {
+        let _ = AuthenticateBuilder::new()
+            .client(&client)
+            .server(server.url().clone())
+            .unwrap()
+            .username("steve@example.com")
+            .password(password)
+            .request()
+            .await;
+    }
+    drop(server);
+
+    let mut client = Client::with_transport(ReplayTransport::new(recorder.cassette()));
+    client.retry_policy(RetryPolicy::none());
+    let resp = AuthenticateBuilder::new()
+        .client(&client)
+        .username("steve@example.com")
+        .password(SEEDED_PASSWORD)
+        .request()
+        .await
+        .unwrap();
+    assert_eq!(resp.access_token, REDACTED);
+    assert_eq!(resp.selected_profile.unwrap().name, "Steve");
+
+    let result = AuthenticateBuilder::new()
+        .client(&client)
+        .username("steve@example.com")
+        .password("WRONG")
+        .request()
+        .await;
+    match result {
+        Err(Error::API(ApiError::ForbiddenOperationException(_), Some(info))) => {
+            assert_eq!(info.status, StatusCode::FORBIDDEN)
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_redaction() {
+    let server = TestServer::start().unwrap();
+    server.add_account(
+        TestAccount::new("herobrine@example.com", "SECRET_PASSWORD").profile("Herobrine"),
+    );
+    let recorder = RecordingTransport::new(ReqwestTransport::default());
+
+    let resp = AuthenticateBuilder::new()
+        .client(&Client::with_transport(recorder.clone()))
+        .server(server.url().clone())
+        .unwrap()
+        .username("herobrine@example.com")
+        .password("SECRET_PASSWORD")
+        .request()
+        .await
+        .unwrap();
+
+    let cassette = recorder.cassette();
+    let text = serde_json::to_string(&cassette).unwrap();
+    assert!(!text.contains(&resp.access_token));
+    assert!(!text.contains("SECRET_PASSWORD"));
+
+    let interaction = &cassette.interactions[0];
+    assert_eq!(
+        interaction.request.body.json.as_ref().unwrap()["password"],
+        REDACTED
+    );
+
+    let resp = auth::parse_response(&interaction.response.to_http()).unwrap();
+    assert_eq!(resp.access_token, REDACTED);
+    assert_eq!(resp.selected_profile.unwrap().name, "Herobrine");
+}
+
+/// Record a cassette of authenticate and refresh requests to a real server.
+///
+/// Run with `cargo test --test cassette -- --ignored`,
+/// and set `CASSETTE_SERVER`, `CASSETTE_USERNAME`, `CASSETTE_PASSWORD`
+/// and `CASSETTE_PATH`.
+#[tokio::test]
+#[ignore]
+async fn test_record() {
+    let server = env::var("CASSETTE_SERVER").unwrap();
+    let username = env::var("CASSETTE_USERNAME").unwrap();
+    let password = env::var("CASSETTE_PASSWORD").unwrap();
+    let recorder = RecordingTransport::new(ReqwestTransport::default());
+    let client = Client::with_transport(recorder.clone());
+
+    let resp = AuthenticateBuilder::new()
+        .client(&client)
+        .server(server.as_str())
+        .unwrap()
+        .endpoint("authenticate")
+        .username(&username)
+        .password(&password)
+        .request_user()
+        .request()
+        .await
+        .unwrap();
+    RefreshBuilder::new()
+        .client(&client)
+        .server(server.as_str())
+        .unwrap()
+        .endpoint("refresh")
+        .access_token(&resp.access_token)
+        .client_token(resp.client_token)
+        .request_user()
+        .request()
+        .await
+        .unwrap();
+
+    recorder
+        .cassette()
+        .save(env::var("CASSETTE_PATH").unwrap())
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_round_trip() {
+    let server = TestServer::start().unwrap();
+    let recorder = RecordingTransport::new(ReqwestTransport::default());
+    AuthenticateBuilder::new()
+        .client(&Client::with_transport(recorder.clone()))
+        .server(server.url().clone())
+        .unwrap()
+        .username("steve@example.com")
+        .password(SEEDED_PASSWORD)
+        .request_user()
+        .request()
+        .await
+        .unwrap();
+
+    let path = env::temp_dir().join(format!("cassette-{}.json", std::process::id()));
+    recorder.cassette().save(&path).unwrap();
+    let cassette = Cassette::load(&path).unwrap();
+    let saved = serde_json::to_string_pretty(&cassette).unwrap() + "\n";
+    assert_eq!(saved, std::fs::read_to_string(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+}
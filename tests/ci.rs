@@ -1,10 +1,9 @@
 use sage_auth::auth::AuthenticateBuilder;
 use sage_auth::invalidate::InvalidateBuilder;
 use sage_auth::refresh::RefreshBuilder;
+use sage_auth::testing::{TestServer, SEEDED_PASSWORD};
 use sage_auth::validate::ValidateBuilder;
 
-use std::env;
-
 macro_rules! assert_ok {
     ($result:expr) => {{
         let result = &$result;
@@ -14,9 +13,14 @@ macro_rules! assert_ok {
 
 #[tokio::test]
 async fn test_all() {
+    let test_server = TestServer::start().unwrap();
+    let server = test_server.url();
+
     let r = AuthenticateBuilder::new()
-        .username(&env::var("MOJANG_ACCOUNT").expect("MOJANG_ACCOUNT is not set"))
-        .password(&env::var("MOJANG_PASSWORD").expect("MOJANG_PASSWORD is not set"))
+        .server(server.clone())
+        .unwrap()
+        .username("steve@example.com")
+        .password(SEEDED_PASSWORD)
         .request_user()
         .request()
         .await;
@@ -27,6 +31,8 @@ async fn test_all() {
 
     assert_ok!(
         ValidateBuilder::new()
+            .server(server.clone())
+            .unwrap()
             .access_token(&resp.access_token)
             .client_token(resp.client_token)
             .request()
@@ -35,6 +41,8 @@ async fn test_all() {
 
     assert_ok!(
         RefreshBuilder::new()
+            .server(server.clone())
+            .unwrap()
             .access_token(&resp.access_token)
             .client_token(resp.client_token)
             .request()
@@ -43,6 +51,8 @@ async fn test_all() {
 
     assert_ok!(
         InvalidateBuilder::new()
+            .server(server.clone())
+            .unwrap()
             .access_token(&resp.access_token)
             .client_token(resp.client_token)
             .request()
@@ -50,6 +60,8 @@ async fn test_all() {
     );
 
     assert!(ValidateBuilder::new()
+        .server(server.clone())
+        .unwrap()
         .access_token(&resp.access_token)
         .client_token(resp.client_token)
         .request()
@@ -0,0 +1,84 @@
+use reqwest::StatusCode;
+use sage_auth::auth::AuthenticateBuilder;
+use sage_auth::client::{Client, RetryPolicy};
+use sage_auth::testing::{TestServer, SEEDED_PASSWORD};
+use sage_auth::validate::ValidateBuilder;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_should_retry_status() {
+    let policy = RetryPolicy::new();
+
+    for &status in &[
+        StatusCode::TOO_MANY_REQUESTS,
+        StatusCode::SERVICE_UNAVAILABLE,
+    ] {
+        assert!(policy.should_retry_status(status, true));
+        assert!(policy.should_retry_status(status, false));
+    }
+    for &status in &[StatusCode::INTERNAL_SERVER_ERROR, StatusCode::BAD_GATEWAY] {
+        assert!(policy.should_retry_status(status, true));
+        assert!(!policy.should_retry_status(status, false));
+    }
+    for &status in &[StatusCode::FORBIDDEN, StatusCode::NOT_IMPLEMENTED] {
+        assert!(!policy.should_retry_status(status, true));
+    }
+}
+
+#[test]
+fn test_backoff() {
+    let mut policy = RetryPolicy::new();
+    policy
+        .base_delay(Duration::from_millis(100))
+        .max_delay(Duration::from_millis(300));
+
+    for _ in 0..20 {
+        let first = policy.backoff(1);
+        assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+        let capped = policy.backoff(10);
+        assert!(capped >= Duration::from_millis(150) && capped <= Duration::from_millis(300));
+    }
+}
+
+#[tokio::test]
+async fn test_retry_after() {
+    let server = Arc::new(TestServer::start().unwrap());
+    let auth = AuthenticateBuilder::new()
+        .server(server.url().clone())
+        .unwrap()
+        .username("steve@example.com")
+        .password(SEEDED_PASSWORD)
+        .request()
+        .await
+        .unwrap();
+    server
+        .status_override("/validate", Some(StatusCode::SERVICE_UNAVAILABLE))
+        .retry_after(Some(Duration::from_secs(0)));
+
+    // The server recovers before the retry
+    let retries = Arc::new(AtomicU32::new(0));
+    let counter = retries.clone();
+    let recovering = server.clone();
+    let mut policy = RetryPolicy::new();
+    policy.on_retry(move |event| {
+        assert_eq!(event.status, Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(event.delay, Duration::from_secs(0));
+        counter.fetch_add(1, Ordering::SeqCst);
+        recovering.status_override("/validate", None);
+    });
+    let mut client = Client::new();
+    client.retry_policy(policy);
+
+    ValidateBuilder::new()
+        .client(&client)
+        .server(server.url().clone())
+        .unwrap()
+        .access_token(&auth.access_token)
+        .client_token(auth.client_token)
+        .request()
+        .await
+        .unwrap();
+    assert_eq!(retries.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,192 @@
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use reqwest::StatusCode;
+use sage_auth::error::MAX_BODY_LENGTH;
+use sage_auth::{ApiError, Error};
+use std::time::Duration;
+
+#[test]
+fn test_api_error() {
+    let body = br#"{
+        "error": "ForbiddenOperationException",
+        "errorMessage": "Invalid credentials. Account migrated, use email as username.",
+        "cause": "UserMigratedException"
+    }"#;
+
+    match Error::from_response_parts(StatusCode::FORBIDDEN, &HeaderMap::new(), body) {
+        Error::API(ApiError::ForbiddenOperationException(message), Some(info)) => {
+            assert!(message.starts_with("Invalid credentials."));
+            assert_eq!(info.status, StatusCode::FORBIDDEN);
+            assert_eq!(info.cause.as_deref(), Some("UserMigratedException"));
+            assert_eq!(info.body.as_bytes(), &body[..]);
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_http_error() {
+    let mut headers = HeaderMap::new();
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+    let body = format!("<html>{}</html>", "x".repeat(2 * MAX_BODY_LENGTH));
+
+    match Error::from_response_parts(StatusCode::TOO_MANY_REQUESTS, &headers, body.as_bytes()) {
+        Error::Http(info) => {
+            assert_eq!(info.status, StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(info.retry_after, Some(Duration::from_secs(30)));
+            assert_eq!(info.body.len(), MAX_BODY_LENGTH);
+            assert!(info.body.starts_with("<html>"));
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    match Error::from_response_parts(StatusCode::BAD_GATEWAY, &HeaderMap::new(), b"") {
+        Error::Http(info) => assert!(info.body.is_empty()),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+struct Case {
+    name: &'static str,
+    status: u16,
+    body: &'static str,
+    invalid_credentials: bool,
+    token_invalid: bool,
+    account_migrated: bool,
+    rate_limited: bool,
+    retryable: bool,
+}
+
+// The Mojang bodies are those documented for the legacy authentication
+// server. Bodies of LittleSkin and Ely.by are to be taken from recorded
+// cassettes, see tests/cassette.rs, rather than guessed here.
+const CASES: &[Case] = &[
+    Case {
+        name: "mojang wrong password",
+        status: 403,
+        body: r#"{"error":"ForbiddenOperationException","errorMessage":"Invalid credentials. Invalid username or password."}"#,
+        invalid_credentials: true,
+        token_invalid: false,
+        account_migrated: false,
+        rate_limited: false,
+        retryable: false,
+    },
+    Case {
+        name: "mojang too many attempts",
+        status: 403,
+        body: r#"{"error":"ForbiddenOperationException","errorMessage":"Invalid credentials."}"#,
+        invalid_credentials: false,
+        token_invalid: false,
+        account_migrated: false,
+        rate_limited: true,
+        retryable: true,
+    },
+    Case {
+        name: "mojang migrated account",
+        status: 403,
+        body: r#"{"error":"ForbiddenOperationException","errorMessage":"Invalid credentials. Account migrated, use email as username.","cause":"UserMigratedException"}"#,
+        invalid_credentials: false,
+        token_invalid: false,
+        account_migrated: true,
+        rate_limited: false,
+        retryable: false,
+    },
+    Case {
+        name: "mojang invalid token",
+        status: 403,
+        body: r#"{"error":"ForbiddenOperationException","errorMessage":"Invalid token."}"#,
+        invalid_credentials: false,
+        token_invalid: true,
+        account_migrated: false,
+        rate_limited: false,
+        retryable: false,
+    },
+    Case {
+        name: "mojang missing credentials",
+        status: 400,
+        body: r#"{"error":"IllegalArgumentException","errorMessage":"credentials is null"}"#,
+        invalid_credentials: false,
+        token_invalid: false,
+        account_migrated: false,
+        rate_limited: false,
+        retryable: false,
+    },
+    Case {
+        name: "services expired token",
+        status: 401,
+        body: "",
+        invalid_credentials: false,
+        token_invalid: true,
+        account_migrated: false,
+        rate_limited: false,
+        retryable: false,
+    },
+    Case {
+        name: "services rate limit",
+        status: 429,
+        body: r#"{"path":"/minecraft/profile/name/Notch/available","errorMessage":"Too many requests"}"#,
+        invalid_credentials: false,
+        token_invalid: false,
+        account_migrated: false,
+        rate_limited: true,
+        retryable: true,
+    },
+    Case {
+        name: "proxy outage",
+        status: 503,
+        body: "<html><body>503 Service Temporarily Unavailable</body></html>",
+        invalid_credentials: false,
+        token_invalid: false,
+        account_migrated: false,
+        rate_limited: false,
+        retryable: true,
+    },
+    Case {
+        name: "server failure",
+        status: 500,
+        body: r#"{"error":"Internal Server Error","errorMessage":"Internal Server Error"}"#,
+        invalid_credentials: false,
+        token_invalid: false,
+        account_migrated: false,
+        rate_limited: false,
+        retryable: true,
+    },
+];
+
+#[test]
+fn test_classification() {
+    for case in CASES {
+        let status = StatusCode::from_u16(case.status).unwrap();
+        let error = Error::from_response_parts(status, &HeaderMap::new(), case.body.as_bytes());
+
+        assert_eq!(
+            error.is_invalid_credentials(),
+            case.invalid_credentials,
+            "{}: invalid credentials",
+            case.name
+        );
+        assert_eq!(
+            error.is_token_invalid(),
+            case.token_invalid,
+            "{}: token invalid",
+            case.name
+        );
+        assert_eq!(
+            error.is_account_migrated(),
+            case.account_migrated,
+            "{}: account migrated",
+            case.name
+        );
+        assert_eq!(
+            error.is_rate_limited(),
+            case.rate_limited,
+            "{}: rate limited",
+            case.name
+        );
+        assert_eq!(
+            error.is_retryable(),
+            case.retryable,
+            "{}: retryable",
+            case.name
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Method, StatusCode};
+use sage_auth::auth::{self, AuthenticateBuilder};
+use sage_auth::http::{HttpRequest, HttpResponse};
+use sage_auth::session;
+use sage_auth::validate::{self, ValidateBuilder};
+use sage_auth::{ApiError, Error};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+#[test]
+fn test_authenticate_request() {
+    let request = AuthenticateBuilder::new()
+        .server("https://auth.example.com/authserver/")
+        .unwrap()
+        .endpoint("authenticate")
+        .username("USERNAME")
+        .password("PASSWORD")
+        .client_token(Uuid::nil())
+        .http_request()
+        .unwrap();
+
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(
+        request.url.as_str(),
+        "https://auth.example.com/authserver/authenticate"
+    );
+    assert_eq!(request.headers[CONTENT_TYPE], "application/json");
+
+    let body: Value = serde_json::from_slice(&request.body).unwrap();
+    assert_eq!(body["username"], "USERNAME");
+    assert_eq!(body["password"], "PASSWORD");
+    assert_eq!(body["clientToken"], Uuid::nil().to_string());
+    assert_eq!(body["agent"], json!({"name": "Minecraft", "version": 1}));
+}
+
+#[test]
+fn test_header() {
+    let url = "https://api.minecraftservices.com/".parse().unwrap();
+    let mut request = HttpRequest::new(Method::GET, url);
+    request
+        .header(AUTHORIZATION, "Bearer ACCESS_TOKEN")
+        .unwrap();
+    assert_eq!(request.headers[AUTHORIZATION], "Bearer ACCESS_TOKEN");
+
+    assert!(matches!(
+        request.header(AUTHORIZATION, "Bearer ACCESS\nTOKEN"),
+        Err(Error::InvalidHeader(_))
+    ));
+}
+
+#[test]
+fn test_join() {
+    let profile_id = Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap();
+    let server = "https://session.example.com/sessionserver/"
+        .parse()
+        .unwrap();
+    let request = session::join_request("ACCESS_TOKEN", profile_id, "-1a2b3c", &server).unwrap();
+
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(
+        request.url.as_str(),
+        "https://session.example.com/session/minecraft/join"
+    );
+    let body: Value = serde_json::from_slice(&request.body).unwrap();
+    assert_eq!(body["accessToken"], "ACCESS_TOKEN");
+    assert_eq!(body["selectedProfile"], "069a79f444e94726a5befca90e38aaf5");
+    assert_eq!(body["serverId"], "-1a2b3c");
+
+    let response = HttpResponse::new(StatusCode::NO_CONTENT, vec![]);
+    session::parse_join_response(&response).unwrap();
+
+    let body = br#"{"error":"ForbiddenOperationException","errorMessage":"Invalid token"}"#;
+    let response = HttpResponse::new(StatusCode::FORBIDDEN, body.to_vec());
+    assert!(session::parse_join_response(&response)
+        .unwrap_err()
+        .is_token_invalid());
+}
+
+#[test]
+fn test_missing_field() {
+    match ValidateBuilder::new().http_request() {
+        Err(Error::MissingField("access_token")) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_response() {
+    let response = HttpResponse::new(
+        StatusCode::OK,
+        br#"{"accessToken":"ACCESS_TOKEN","clientToken":"00000000-0000-0000-0000-000000000000","availableProfiles":[]}"#.to_vec(),
+    );
+    let resp = auth::parse_response(&response).unwrap();
+    assert_eq!(resp.access_token, "ACCESS_TOKEN");
+    assert!(resp.selected_profile.is_none());
+
+    let response = HttpResponse::new(StatusCode::OK, b"{\"accessToken\":".to_vec());
+    assert!(matches!(
+        auth::parse_response(&response),
+        Err(Error::Json(_))
+    ));
+
+    let response = HttpResponse::new(
+        StatusCode::FORBIDDEN,
+        br#"{"error":"ForbiddenOperationException","errorMessage":"Invalid token."}"#.to_vec(),
+    );
+    match validate::parse_response(&response) {
+        Err(Error::API(ApiError::ForbiddenOperationException(message), Some(info))) => {
+            assert_eq!(message, "Invalid token.");
+            assert_eq!(info.status, StatusCode::FORBIDDEN);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let response = HttpResponse::new(StatusCode::NO_CONTENT, vec![]);
+    validate::parse_response(&response).unwrap();
+}
@@ -0,0 +1,81 @@
+use sage_auth::launch::{offline_uuid, Argument, LaunchArguments, UserType};
+use uuid::Uuid;
+
+const GAME_ARGUMENTS: &str = r#"[
+    "--username", "${auth_player_name}",
+    "--uuid", "${auth_uuid}",
+    "--accessToken", "${auth_access_token}",
+    "--userType", "${user_type}",
+    "--versionType", "${version_type}",
+    {
+        "rules": [{"action": "allow", "features": {"is_demo_user": true}}],
+        "value": "--demo"
+    },
+    {
+        "rules": [{"action": "allow", "features": {"has_custom_resolution": true}}],
+        "value": ["--width", "${resolution_width}", "--height", "${resolution_height}"]
+    }
+]"#;
+
+#[test]
+fn test_offline_uuid() {
+    assert_eq!(
+        offline_uuid("Notch"),
+        Uuid::parse_str("b50ad385-829d-3141-a216-7e7d7539ba7f").unwrap()
+    );
+}
+
+#[test]
+fn test_game_arguments() {
+    let arguments: Vec<Argument> = serde_json::from_str(GAME_ARGUMENTS).unwrap();
+    let uuid = Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap();
+
+    let mut launch = LaunchArguments::new("Notch", uuid, "TOKEN");
+    launch.user_type(UserType::Msa);
+    assert_eq!(
+        launch.game_arguments(&arguments),
+        vec![
+            "--username",
+            "Notch",
+            "--uuid",
+            "069a79f444e94726a5befca90e38aaf5",
+            "--accessToken",
+            "TOKEN",
+            "--userType",
+            "msa",
+            "--versionType",
+            "${version_type}",
+        ]
+    );
+
+    launch
+        .demo(true)
+        .feature("has_custom_resolution", true)
+        .variable("resolution_width", "854")
+        .variable("resolution_height", "480");
+    assert_eq!(
+        launch.game_arguments(&arguments)[10..],
+        ["--demo", "--width", "854", "--height", "480"]
+    );
+}
+
+#[test]
+fn test_substitutions() {
+    let mut launch = LaunchArguments::offline("Steve");
+    launch.user_property("twitch_access_token", "abc");
+    let substitutions = launch.substitutions();
+
+    assert_eq!(substitutions["user_type"], "legacy");
+    assert_eq!(
+        substitutions["auth_session"],
+        format!("token:0:{}", offline_uuid("Steve").to_simple())
+    );
+    assert_eq!(
+        substitutions["user_properties"],
+        r#"{"twitch_access_token":["abc"]}"#
+    );
+    assert_eq!(
+        launch.legacy_game_arguments("--username ${auth_player_name} --session ${auth_session"),
+        vec!["--username", "Steve", "--session", "${auth_session"]
+    );
+}
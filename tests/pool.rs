@@ -0,0 +1,89 @@
+use reqwest::{Method, StatusCode, Url};
+use sage_auth::client::{Client, RetryPolicy};
+use sage_auth::pool::ServerPool;
+use sage_auth::session::Session;
+use sage_auth::testing::{TestServer, SEEDED_PASSWORD};
+use sage_auth::transport::{MockResponse, MockTransport};
+use std::net::TcpListener;
+use std::time::Duration;
+
+fn dead_server() -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap()
+}
+
+#[test]
+fn test_candidates() {
+    let first = Url::parse("https://auth1.example.com").unwrap();
+    let second = Url::parse("https://auth2.example.com").unwrap();
+    let third = Url::parse("https://auth3.example.com").unwrap();
+    let pool = ServerPool::new(vec![first.clone(), second.clone(), third.clone()]).unwrap();
+
+    pool.mark_unhealthy(&second);
+    pool.mark_unhealthy(&first);
+    assert!(!pool.is_healthy(&first));
+    assert_eq!(
+        pool.candidates(),
+        vec![third.clone(), second.clone(), first.clone()]
+    );
+
+    pool.mark_healthy(&first);
+    assert_eq!(pool.candidates(), vec![first, third, second]);
+
+    assert!(ServerPool::new(Vec::<Url>::new()).is_err());
+}
+
+#[test]
+fn test_cooldown() {
+    let server = Url::parse("https://auth1.example.com").unwrap();
+    let mut pool = ServerPool::single(server.clone());
+    pool.cooldown(Duration::from_secs(0));
+
+    pool.mark_unhealthy(&server);
+    assert!(pool.is_healthy(&server));
+}
+
+#[tokio::test]
+async fn test_failover() {
+    let dead = dead_server();
+    let failing = TestServer::start().unwrap();
+    failing.status_override("/authenticate", Some(StatusCode::SERVICE_UNAVAILABLE));
+    let live = TestServer::start().unwrap();
+
+    let failing = failing.url().clone();
+    let pool = ServerPool::new(vec![dead.clone(), failing.clone(), live.url().clone()]).unwrap();
+    let mut client = Client::new();
+    client.retry_policy(RetryPolicy::none());
+
+    let session = Session::login_with_pool(&client, &pool, "steve@example.com", SEEDED_PASSWORD)
+        .await
+        .unwrap();
+    assert_eq!(session.auth_server(), live.url());
+    session.validate().await.unwrap();
+
+    assert!(!pool.is_healthy(&dead));
+    assert!(!pool.is_healthy(&failing));
+    assert!(pool.is_healthy(live.url()));
+    assert_eq!(&pool.candidates()[0], live.url());
+}
+
+#[tokio::test]
+async fn test_no_failover_after_timeout() {
+    let first = Url::parse("https://auth1.example.com").unwrap();
+    let second = Url::parse("https://auth2.example.com").unwrap();
+    let pool = ServerPool::new(vec![first.clone(), second]).unwrap();
+
+    // The first server may have issued a token before timing out.
+    let mut mock = MockTransport::new();
+    mock.on(Method::POST, "/authenticate", MockResponse::Timeout);
+    let mut client = Client::with_transport(mock.clone());
+    client.retry_policy(RetryPolicy::none());
+
+    let error = Session::login_with_pool(&client, &pool, "USERNAME", "PASSWORD")
+        .await
+        .unwrap_err();
+    assert!(error.is_timeout());
+    assert_eq!(mock.requests().len(), 1);
+    assert_eq!(mock.requests()[0].url.host_str(), first.host_str());
+    assert!(!pool.is_healthy(&first));
+}
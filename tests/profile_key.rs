@@ -0,0 +1,69 @@
+use chrono::{Duration, Utc};
+use sage_auth::error::ProfileKeyError;
+use sage_auth::profile_key::{ProfilePublicKey, ServicesKeys};
+use sage_auth::signing::SigningKey;
+use sage_auth::Error;
+use uuid::Uuid;
+
+fn rejection(result: sage_auth::Result<sage_auth::signing::PublicKey>) -> ProfileKeyError {
+    match result {
+        Err(Error::ProfileKey(error)) => error,
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_verify_profile_key() {
+    let mojang_key = SigningKey::generate(1024).unwrap();
+    let player_key = SigningKey::generate(1024).unwrap();
+    let keys = ServicesKeys {
+        profile_property_keys: vec![],
+        player_certificate_keys: vec![mojang_key.public_key()],
+    };
+    let profile_id = Uuid::new_v4();
+
+    let mut key = ProfilePublicKey {
+        expires_at: (Utc::now() + Duration::hours(1)).timestamp_millis(),
+        key: player_key.public_key().to_der().unwrap(),
+        signature: vec![],
+    };
+    key.signature = mojang_key.sign_bytes(&key.signed_payload(profile_id));
+
+    let verified = keys.verify(profile_id, &key, Duration::zero()).unwrap();
+    assert_eq!(verified, player_key.public_key());
+    assert_eq!(
+        rejection(keys.verify(Uuid::new_v4(), &key, Duration::zero())),
+        ProfileKeyError::InvalidSignature
+    );
+    assert_eq!(
+        rejection(keys.verify_legacy(&key, Duration::zero())),
+        ProfileKeyError::InvalidSignature
+    );
+
+    key.expires_at = (Utc::now() - Duration::minutes(1)).timestamp_millis();
+    key.signature = mojang_key.sign_bytes(&key.signed_payload(profile_id));
+    assert_eq!(
+        rejection(keys.verify(profile_id, &key, Duration::zero())),
+        ProfileKeyError::Expired
+    );
+    assert!(keys.verify(profile_id, &key, Duration::minutes(5)).is_ok());
+}
+
+#[test]
+fn test_legacy_signed_payload() {
+    let key = ProfilePublicKey {
+        expires_at: 1_656_000_000_000,
+        key: vec![0; 100],
+        signature: vec![],
+    };
+    let payload = String::from_utf8(key.legacy_signed_payload()).unwrap();
+
+    assert_eq!(
+        payload,
+        format!(
+            "1656000000000-----BEGIN RSA PUBLIC KEY-----\n{}\n{}\n-----END RSA PUBLIC KEY-----\n",
+            "A".repeat(76),
+            "A".repeat(58) + "=="
+        )
+    );
+}
@@ -0,0 +1,65 @@
+use reqwest::Url;
+use sage_auth::rate_limit::{EndpointGroup, Quota, RateLimiter};
+use std::time::Duration;
+
+fn url(url: &str) -> Url {
+    Url::parse(url).unwrap()
+}
+
+#[test]
+fn test_endpoint_group() {
+    for (endpoint, group) in &[
+        ("https://authserver.mojang.com/authenticate", EndpointGroup::Auth),
+        (
+            "https://littleskin.cn/api/yggdrasil/authserver/refresh",
+            EndpointGroup::Auth,
+        ),
+        (
+            "https://sessionserver.mojang.com/session/minecraft/join",
+            EndpointGroup::Session,
+        ),
+        (
+            "https://sessionserver.mojang.com/session/minecraft/profile/069a79f444e94726a5befca90e38aaf5",
+            EndpointGroup::Profile,
+        ),
+        (
+            "https://api.mojang.com/users/profiles/minecraft/Notch",
+            EndpointGroup::Profile,
+        ),
+        (
+            "https://api.minecraftservices.com/minecraft/profile",
+            EndpointGroup::Other,
+        ),
+    ] {
+        assert_eq!(EndpointGroup::of(&url(endpoint)), *group, "{}", endpoint);
+    }
+}
+
+#[test]
+fn test_reserve() {
+    let mut limiter = RateLimiter::unlimited();
+    limiter.quota(
+        EndpointGroup::Auth,
+        Some(Quota {
+            requests: 2,
+            period: Duration::from_secs(2),
+        }),
+    );
+
+    let auth = url("https://authserver.mojang.com/authenticate");
+    assert_eq!(limiter.reserve(&auth), Duration::from_secs(0));
+    assert_eq!(limiter.reserve(&auth), Duration::from_secs(0));
+
+    let first = limiter.reserve(&auth);
+    assert!(first > Duration::from_millis(900) && first <= Duration::from_secs(1));
+    let second = limiter.reserve(&auth);
+    assert!(second > Duration::from_millis(1900) && second <= Duration::from_secs(2));
+
+    // Other servers and groups have their own buckets
+    let mirror = url("https://auth.example.com/authenticate");
+    assert_eq!(limiter.reserve(&mirror), Duration::from_secs(0));
+    let services = url("https://api.minecraftservices.com/minecraft/profile");
+    for _ in 0..10 {
+        assert_eq!(limiter.reserve(&services), Duration::from_secs(0));
+    }
+}
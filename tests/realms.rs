@@ -0,0 +1,165 @@
+use reqwest::header::{HeaderValue, COOKIE, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+use sage_auth::client::{Client, RetryPolicy};
+use sage_auth::http::HttpResponse;
+use sage_auth::realms::{Compatibility, RealmsClient};
+use sage_auth::transport::{MockResponse, MockTransport};
+use sage_auth::Error;
+use serde_json::json;
+use uuid::Uuid;
+
+fn realms(mock: &MockTransport) -> RealmsClient {
+    let mut client = Client::with_transport(mock.clone());
+    client.retry_policy(RetryPolicy::none());
+    let profile_id = Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap();
+    let mut realms = RealmsClient::new("ACCESS_TOKEN", profile_id, "Notch", "1.20.4");
+    realms.client(&client);
+    realms
+}
+
+fn starting() -> MockResponse {
+    let mut response = HttpResponse::new(
+        StatusCode::SERVICE_UNAVAILABLE,
+        b"Retry again later".to_vec(),
+    );
+    response.headers.insert(RETRY_AFTER, HeaderValue::from(0));
+    MockResponse::Response(response)
+}
+
+#[tokio::test]
+async fn test_join_address() {
+    let mut mock = MockTransport::new();
+    mock.on(Method::GET, "/worlds/v1/42/join/pc", starting())
+        .on(Method::GET, "/worlds/v1/42/join/pc", starting())
+        .on(
+            Method::GET,
+            "/worlds/v1/42/join/pc",
+            MockResponse::json(
+                StatusCode::OK,
+                &json!({"address": "127.0.0.1:25565", "pendingUpdate": false}),
+            ),
+        );
+
+    let address = realms(&mock).join_address(42).await.unwrap();
+    assert_eq!(address.address, "127.0.0.1:25565");
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(
+        requests[0].headers[COOKIE],
+        "sid=token:ACCESS_TOKEN:069a79f444e94726a5befca90e38aaf5;user=Notch;version=1.20.4"
+    );
+}
+
+#[tokio::test]
+async fn test_join_attempts() {
+    let mut mock = MockTransport::new();
+    mock.on(Method::GET, "/worlds/v1/42/join/pc", starting());
+
+    // The default retry policy does not repeat the join on top of its attempts.
+    let profile_id = Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap();
+    let mut realms = RealmsClient::new("ACCESS_TOKEN", profile_id, "Notch", "1.20.4");
+    let error = realms
+        .client(&Client::with_transport(mock.clone()))
+        .join_attempts(3)
+        .join_address(42)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.response_info().unwrap().status,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert_eq!(mock.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_availability() {
+    let mut mock = MockTransport::new();
+    mock.on(
+        Method::GET,
+        "/mco/available",
+        MockResponse::body(StatusCode::OK, "true"),
+    )
+    .on(
+        Method::GET,
+        "/mco/client/compatible",
+        MockResponse::body(StatusCode::OK, "COMPATIBLE"),
+    )
+    .on(
+        Method::GET,
+        "/mco/client/compatible",
+        MockResponse::body(StatusCode::OK, "OUTDATED"),
+    )
+    .on(
+        Method::GET,
+        "/mco/client/compatible",
+        MockResponse::body(StatusCode::OK, "OTHER"),
+    );
+    let realms = realms(&mock);
+
+    assert!(realms.is_available().await.unwrap());
+    assert_eq!(
+        realms.compatibility().await.unwrap(),
+        Compatibility::Compatible
+    );
+    assert_eq!(
+        realms.compatibility().await.unwrap(),
+        Compatibility::Outdated
+    );
+    assert_eq!(realms.compatibility().await.unwrap(), Compatibility::Other);
+}
+
+#[tokio::test]
+async fn test_invites() {
+    let mut mock = MockTransport::new();
+    mock.on(
+        Method::GET,
+        "/invites/count/pending",
+        MockResponse::body(StatusCode::OK, "1"),
+    )
+    .on(
+        Method::GET,
+        "/invites/count/pending",
+        MockResponse::body(StatusCode::OK, "many"),
+    )
+    .on(
+        Method::GET,
+        "/invites/pending",
+        MockResponse::json(
+            StatusCode::OK,
+            &json!({"invites": [{
+                "invitationId": "17",
+                "worldName": "Realm",
+                "worldOwnerName": "jeb_",
+                "worldOwnerUuid": "853c80ef3c3749fdaa49938b674adae6",
+                "date": 1_700_000_000_000i64,
+            }]}),
+        ),
+    )
+    .on(
+        Method::PUT,
+        "/invites/accept/17",
+        MockResponse::status(StatusCode::NO_CONTENT),
+    )
+    .on(
+        Method::PUT,
+        "/invites/reject/18",
+        MockResponse::status(StatusCode::NOT_FOUND),
+    );
+    let realms = realms(&mock);
+
+    assert_eq!(realms.pending_invites_count().await.unwrap(), 1);
+    assert!(matches!(
+        realms.pending_invites_count().await,
+        Err(Error::Json(_))
+    ));
+
+    let invites = realms.pending_invites().await.unwrap();
+    assert_eq!(invites[0].world_owner_name, "jeb_");
+    realms
+        .accept_invite(&invites[0].invitation_id)
+        .await
+        .unwrap();
+    assert!(realms.reject_invite("18").await.is_err());
+    assert_eq!(mock.requests()[3].method, Method::PUT);
+}
@@ -0,0 +1,90 @@
+use sage_auth::chat::ChatMessage;
+use sage_auth::error::ReportError;
+use sage_auth::report::{ReportBuilder, ReportReason, ReportedMessage, MAX_COMMENTS_LENGTH};
+use sage_auth::Error;
+use uuid::Uuid;
+
+fn message(index: i32) -> ChatMessage {
+    ChatMessage {
+        index,
+        sender: Uuid::nil(),
+        session_id: Uuid::nil(),
+        salt: 42,
+        timestamp: 1_700_000_000_000,
+        message: "hello".to_owned(),
+        last_seen: vec![vec![1, 2, 3]],
+    }
+}
+
+fn rejection(builder: &ReportBuilder) -> ReportError {
+    match builder.build() {
+        Err(Error::Report(error)) => error,
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_chat_report() {
+    let mut builder = ReportBuilder::chat(Uuid::nil());
+    builder
+        .reason(ReportReason::HateSpeech)
+        .client_version("1.20.4")
+        .server_address("mc.example.com")
+        .message(&message(0), &[0xff; 4], false);
+    assert!(matches!(rejection(&builder), ReportError::Invalid(_)));
+
+    builder.message(&message(1), &[0xff; 4], true);
+    let report = serde_json::to_value(builder.build().unwrap()).unwrap();
+
+    assert_eq!(report["version"], 1);
+    assert_eq!(report["report"]["type"], "CHAT");
+    assert_eq!(report["report"]["reason"], "hate_speech");
+    assert_eq!(report["clientInfo"]["locale"], "en-US");
+    assert_eq!(report["thirdPartyServerInfo"]["address"], "mc.example.com");
+    assert!(report.get("realmInfo").is_none());
+
+    let messages = report["report"]["evidence"]["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1]["index"], 1);
+    assert_eq!(messages[1]["messageReported"], true);
+    assert_eq!(messages[1]["signature"], "/////w==");
+    assert_eq!(messages[1]["lastSeen"][0], "AQID");
+    assert_eq!(messages[1]["timestamp"], "2023-11-14T22:13:20Z");
+}
+
+#[test]
+fn test_report_limits() {
+    let mut builder = ReportBuilder::skin(Uuid::nil(), "http://textures.minecraft.net/texture/0");
+    assert!(matches!(
+        builder.build(),
+        Err(Error::MissingField("reason"))
+    ));
+
+    builder
+        .reason(ReportReason::SexuallyInappropriate)
+        .client_version("1.20.4")
+        .comments("a".repeat(MAX_COMMENTS_LENGTH + 1));
+    assert!(matches!(rejection(&builder), ReportError::Invalid(_)));
+
+    builder.comments("a".repeat(MAX_COMMENTS_LENGTH));
+    let report = serde_json::to_value(builder.build().unwrap()).unwrap();
+    assert_eq!(report["report"]["type"], "SKIN");
+    assert!(report["report"].get("evidence").is_none());
+}
+
+#[test]
+fn test_invalid_timestamp() {
+    let mut message = message(0);
+    message.timestamp = i64::MAX;
+    assert!(matches!(
+        ReportedMessage::new(&message, &[0xff; 4], true),
+        Err(Error::Report(ReportError::Invalid(_)))
+    ));
+
+    let mut builder = ReportBuilder::chat(Uuid::nil());
+    builder
+        .reason(ReportReason::HateSpeech)
+        .client_version("1.20.4")
+        .message(&message, &[0xff; 4], true);
+    assert!(matches!(rejection(&builder), ReportError::Invalid(_)));
+}
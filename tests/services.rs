@@ -0,0 +1,367 @@
+use reqwest::header::AUTHORIZATION;
+use reqwest::{Method, StatusCode};
+use sage_auth::client::{Client, RetryPolicy};
+use sage_auth::error::NameError;
+use sage_auth::services::{validate_name, validate_skin, ServicesClient, TextureState};
+use sage_auth::signing::SigningKey;
+use sage_auth::transport::{MockResponse, MockTransport};
+use sage_auth::{ApiError, Error};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+fn services(mock: &MockTransport) -> ServicesClient {
+    let mut client = Client::with_transport(mock.clone());
+    client.retry_policy(RetryPolicy::none());
+    let mut services = ServicesClient::new("ACCESS_TOKEN");
+    services.client(&client);
+    services
+}
+
+fn profile() -> Value {
+    json!({
+        "id": Uuid::nil().to_simple().to_string(),
+        "name": "Steve",
+        "skins": [],
+        "capes": [
+            {"id": "CAPE_ID", "state": "ACTIVE", "url": "http://textures.minecraft.net/texture/1", "alias": "Migrator"},
+            {"id": "OTHER_ID", "state": "INACTIVE", "url": "http://textures.minecraft.net/texture/2", "alias": "Vanilla"},
+        ],
+    })
+}
+
+fn body(mock: &MockTransport, index: usize) -> Value {
+    serde_json::from_slice(&mock.requests()[index].body).unwrap()
+}
+
+fn name_change_info(allowed: bool) -> MockResponse {
+    MockResponse::json(
+        StatusCode::OK,
+        &json!({
+            "changedAt": "2023-01-01T00:00:00Z",
+            "createdAt": "2020-01-01T00:00:00Z",
+            "nameChangeAllowed": allowed,
+        }),
+    )
+}
+
+fn png_header(width: u32, height: u32) -> Vec<u8> {
+    let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+    png.extend_from_slice(&width.to_be_bytes());
+    png.extend_from_slice(&height.to_be_bytes());
+    png.extend_from_slice(&[8, 6, 0, 0, 0]);
+    png
+}
+
+#[test]
+fn test_validate_skin() {
+    assert!(validate_skin(&png_header(64, 64)).is_ok());
+    assert!(validate_skin(&png_header(64, 32)).is_ok());
+
+    match validate_skin(&png_header(128, 128)) {
+        Err(Error::InvalidSkin(message)) => {
+            assert_eq!(message, "skin must be 64x64 or 64x32, got 128x128")
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(validate_skin(b"GIF89a").is_err());
+}
+
+#[test]
+fn test_validate_name() {
+    assert!(validate_name("Notch").is_ok());
+    assert!(validate_name("a_b").is_ok());
+    assert!(validate_name("Sixteen_chars_16").is_ok());
+
+    for name in &[
+        "ab",
+        "Seventeen_chars17",
+        "white space",
+        "Ünicode",
+        "dash-name",
+        "",
+    ] {
+        assert!(matches!(
+            validate_name(name),
+            Err(Error::Name(NameError::Invalid))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_change_name() {
+    let mut mock = MockTransport::new();
+    mock.on(
+        Method::PUT,
+        "/minecraft/profile/name/Taken",
+        MockResponse::json(
+            StatusCode::FORBIDDEN,
+            &json!({"error": "FORBIDDEN", "details": {"status": "DUPLICATE"}}),
+        ),
+    )
+    .on(
+        Method::PUT,
+        "/minecraft/profile/name/TooSoon",
+        MockResponse::json(StatusCode::FORBIDDEN, &json!({"error": "FORBIDDEN"})),
+    )
+    .on(
+        Method::GET,
+        "/minecraft/profile/namechange",
+        name_change_info(false),
+    );
+    let services = services(&mock);
+
+    assert!(matches!(
+        services.change_name("Taken").await,
+        Err(Error::Name(NameError::Taken))
+    ));
+    assert!(matches!(
+        services.change_name("TooSoon").await,
+        Err(Error::Name(NameError::TooSoon))
+    ));
+}
+
+#[tokio::test]
+async fn test_change_name_api_error() {
+    let mut mock = MockTransport::new();
+    mock.on(
+        Method::PUT,
+        "/minecraft/profile/name/Steve",
+        MockResponse::json(
+            StatusCode::FORBIDDEN,
+            &json!({"error": "ForbiddenOperationException", "errorMessage": "Invalid token."}),
+        ),
+    )
+    .on(
+        Method::GET,
+        "/minecraft/profile/namechange",
+        name_change_info(true),
+    );
+
+    match services(&mock).change_name("Steve").await {
+        Err(Error::API(ApiError::ForbiddenOperationException(message), _)) => {
+            assert_eq!(message, "Invalid token.")
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_capes() {
+    let mut mock = MockTransport::new();
+    mock.on(
+        Method::GET,
+        "/minecraft/profile",
+        MockResponse::json(StatusCode::OK, &profile()),
+    )
+    .on(
+        Method::PUT,
+        "/minecraft/profile/capes/active",
+        MockResponse::json(StatusCode::OK, &profile()),
+    )
+    .on(
+        Method::DELETE,
+        "/minecraft/profile/capes/active",
+        MockResponse::json(StatusCode::OK, &json!({"id": Uuid::nil(), "name": "Steve"})),
+    );
+    let services = services(&mock);
+
+    let capes = services.capes().await.unwrap();
+    assert_eq!(capes.len(), 2);
+    assert_eq!(capes[0].state, TextureState::Active);
+    assert_eq!(capes[1].alias.as_deref(), Some("Vanilla"));
+    assert_eq!(
+        mock.requests()[0].headers[AUTHORIZATION],
+        "Bearer ACCESS_TOKEN"
+    );
+
+    services.equip_cape("OTHER_ID").await.unwrap();
+    assert_eq!(body(&mock, 1), json!({"capeId": "OTHER_ID"}));
+
+    let profile = services.hide_cape().await.unwrap();
+    assert!(profile.capes.is_empty());
+    assert_eq!(mock.requests()[2].method, Method::DELETE);
+}
+
+#[tokio::test]
+async fn test_player_certificates() {
+    let key = SigningKey::generate(1024).unwrap();
+    let certificates = |signature: &str| {
+        MockResponse::json(
+            StatusCode::OK,
+            &json!({
+                "keyPair": {
+                    "privateKey": key.to_pem().unwrap(),
+                    "publicKey": key.public_key().to_pem().unwrap(),
+                },
+                "publicKeySignature": "AQID",
+                "publicKeySignatureV2": signature,
+                "expiresAt": "2030-01-02T00:00:00.000000Z",
+                "refreshedAfter": "2030-01-01T00:00:00.000000Z",
+            }),
+        )
+    };
+    let mut mock = MockTransport::new();
+    mock.on(Method::POST, "/player/certificates", certificates("BAUG"))
+        .on(
+            Method::POST,
+            "/player/certificates",
+            certificates("not base64!"),
+        );
+    let services = services(&mock);
+
+    let certificates = services.player_certificates().await.unwrap();
+    assert_eq!(certificates.public_key, key.public_key());
+    assert_eq!(certificates.public_key_signature, vec![1, 2, 3]);
+    assert_eq!(certificates.public_key_signature_v2, vec![4, 5, 6]);
+    assert!(!certificates.is_expired() && !certificates.needs_refresh());
+    assert!(mock.requests()[0].body.is_empty());
+
+    assert!(matches!(
+        services.player_certificates().await,
+        Err(Error::InvalidSignature)
+    ));
+}
+
+#[tokio::test]
+async fn test_player_attributes() {
+    let attributes = |profanity_filter_on: bool| {
+        MockResponse::json(
+            StatusCode::OK,
+            &json!({
+                "privileges": {
+                    "onlineChat": {"enabled": true},
+                    "multiplayerServer": {"enabled": true},
+                    "multiplayerRealms": {"enabled": false},
+                    "telemetry": {"enabled": true},
+                },
+                "profanityFilterPreferences": {"profanityFilterOn": profanity_filter_on},
+                "banStatus": {
+                    "bannedScopes": {
+                        "MULTIPLAYER": {
+                            "banId": Uuid::nil(),
+                            "expires": null,
+                            "reason": "hate_speech",
+                            "reasonMessage": null,
+                        },
+                    },
+                },
+            }),
+        )
+    };
+    let mut mock = MockTransport::new();
+    mock.on(Method::GET, "/player/attributes", attributes(false))
+        .on(Method::POST, "/player/attributes", attributes(true));
+    let services = services(&mock);
+
+    let attributes = services.player_attributes().await.unwrap();
+    assert!(attributes.privileges.online_chat.enabled);
+    assert!(!attributes.privileges.multiplayer_realms.enabled);
+    assert!(!attributes.profanity_filter_preferences.profanity_filter_on);
+    let ban = attributes.multiplayer_ban().unwrap();
+    assert_eq!(ban.reason, "hate_speech");
+    assert!(ban.expires.is_none());
+
+    let attributes = services.set_profanity_filter(true).await.unwrap();
+    assert!(attributes.profanity_filter_preferences.profanity_filter_on);
+    assert_eq!(
+        body(&mock, 1),
+        json!({"profanityFilterPreferences": {"profanityFilterOn": true}})
+    );
+}
+
+#[tokio::test]
+async fn test_entitlements() {
+    let mut mock = MockTransport::new();
+    mock.on(
+        Method::GET,
+        "/entitlements/mcstore",
+        MockResponse::json(
+            StatusCode::OK,
+            &json!({
+                "items": [
+                    {"name": "product_minecraft", "signature": "JWT"},
+                    {"name": "game_minecraft", "signature": "JWT"},
+                ],
+                "signature": "JWT",
+                "keyId": "1",
+            }),
+        ),
+    )
+    .on(
+        Method::GET,
+        "/entitlements/license",
+        MockResponse::json(
+            StatusCode::OK,
+            &json!({
+                "items": [
+                    {"name": "product_minecraft", "source": "GAMEPASS"},
+                    {"name": "game_minecraft", "source": "GAMEPASS"},
+                ],
+                "signature": "JWT",
+                "keyId": "1",
+            }),
+        ),
+    );
+    let client = services(&mock);
+
+    let entitlements = client.entitlements().await.unwrap();
+    assert!(entitlements.owns_minecraft());
+    assert!(!entitlements.has_game_pass());
+
+    let license = client.license().await.unwrap();
+    assert!(license.owns_minecraft() && license.has_game_pass());
+    let query = mock.requests()[1].url.query().unwrap().to_owned();
+    assert!(query.starts_with("requestId="));
+    Uuid::parse_str(&query["requestId=".len()..]).unwrap();
+
+    let mut mock = MockTransport::new();
+    mock.on(
+        Method::GET,
+        "/entitlements/mcstore",
+        MockResponse::json(
+            StatusCode::UNAUTHORIZED,
+            &json!({"path": "/entitlements/mcstore", "error": "UNAUTHORIZED", "errorMessage": ""}),
+        ),
+    );
+    let error = services(&mock).entitlements().await.unwrap_err();
+    assert_eq!(
+        error.response_info().unwrap().status,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn test_create_profile() {
+    let mut mock = MockTransport::new();
+    mock.on(
+        Method::GET,
+        "/minecraft/profile/name/Steve/available",
+        MockResponse::json(StatusCode::OK, &json!({"status": "AVAILABLE"})),
+    )
+    .on(
+        Method::GET,
+        "/minecraft/profile/name/Alex/available",
+        MockResponse::json(StatusCode::OK, &json!({"status": "DUPLICATE"})),
+    )
+    .on(
+        Method::POST,
+        "/minecraft/profile",
+        MockResponse::json(StatusCode::OK, &profile()),
+    );
+    let services = services(&mock);
+
+    let profile = services.create_profile("Steve").await.unwrap();
+    assert_eq!(profile.name, "Steve");
+    assert_eq!(body(&mock, 1), json!({"profileName": "Steve"}));
+
+    mock.clear_requests();
+    assert!(matches!(
+        services.create_profile("Alex").await,
+        Err(Error::Name(NameError::Taken))
+    ));
+    assert!(matches!(
+        services.create_profile("no").await,
+        Err(Error::Name(NameError::Invalid))
+    ));
+    assert_eq!(mock.requests().len(), 1);
+}
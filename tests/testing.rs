@@ -0,0 +1,173 @@
+use reqwest::StatusCode;
+use sage_auth::client::{Client, RetryPolicy};
+use sage_auth::pool::ServerPool;
+use sage_auth::session::{AccountState, Session};
+use sage_auth::testing::{TestAccount, TestServer, SEEDED_PASSWORD};
+use sage_auth::Error;
+use std::time::Duration;
+
+async fn login(server: &TestServer, username: &str) -> Session {
+    let mut client = Client::new();
+    client.retry_policy(RetryPolicy::none());
+    let pool = ServerPool::single(server.url().clone());
+
+    let mut session = Session::login_with_pool(&client, &pool, username, SEEDED_PASSWORD)
+        .await
+        .unwrap();
+    session
+        .session_server(server.url().clone())
+        .unwrap()
+        .services_server(server.url().clone())
+        .unwrap();
+    session
+}
+
+#[tokio::test]
+async fn test_session() {
+    let server = TestServer::start().unwrap();
+    let session = login(&server, "steve@example.com").await;
+
+    let profile = session.profile().unwrap();
+    assert_eq!(profile.name, "Steve");
+    assert_eq!(
+        Some(profile.id),
+        server
+            .account("steve@example.com")
+            .and_then(|account| account.profile)
+            .map(|profile| profile.id)
+    );
+    assert_eq!(session.account_state().await.unwrap(), AccountState::Owned);
+    assert_eq!(session.services().profile().await.unwrap().name, "Steve");
+
+    session.join_server("SERVER_HASH").await.unwrap();
+    let url = server
+        .url()
+        .join("/session/minecraft/hasJoined?username=Steve&serverId=SERVER_HASH")
+        .unwrap();
+    let response = reqwest::get(url).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_accounts() {
+    let server = TestServer::start().unwrap();
+    server.add_account(
+        TestAccount::new("herobrine@example.com", SEEDED_PASSWORD).profile("Herobrine"),
+    );
+
+    let session = login(&server, "demo@example.com").await;
+    assert_eq!(session.account_state().await.unwrap(), AccountState::Demo);
+    let session = login(&server, "new@example.com").await;
+    assert_eq!(
+        session.account_state().await.unwrap(),
+        AccountState::NoProfile
+    );
+    let session = login(&server, "herobrine@example.com").await;
+    assert_eq!(session.profile().unwrap().name, "Herobrine");
+
+    // Services endpoints which are not emulated
+    let error = session.services().player_certificates().await.unwrap_err();
+    assert_eq!(error.response_info().unwrap().status, StatusCode::NOT_FOUND);
+    let error = session.services().hide_cape().await.unwrap_err();
+    assert_eq!(error.response_info().unwrap().status, StatusCode::NOT_FOUND);
+
+    // A profile whose Game Pass expired
+    server.add_account(
+        TestAccount::new("lapsed@example.com", SEEDED_PASSWORD)
+            .profile("Lapsed")
+            .owns_game(false),
+    );
+    let session = login(&server, "lapsed@example.com").await;
+    assert!(session.profile().is_some());
+    assert_eq!(session.account_state().await.unwrap(), AccountState::Demo);
+
+    let mut client = Client::new();
+    client.retry_policy(RetryPolicy::none());
+    let pool = ServerPool::single(server.url().clone());
+    let error = Session::login_with_pool(&client, &pool, "steve@example.com", "WRONG")
+        .await
+        .unwrap_err();
+    assert!(error.is_invalid_credentials());
+}
+
+#[tokio::test]
+async fn test_token_expiry() {
+    let server = TestServer::start().unwrap();
+    let mut session = login(&server, "steve@example.com").await;
+    session.validate().await.unwrap();
+
+    server.expire_tokens();
+    assert!(session.validate().await.unwrap_err().is_token_invalid());
+    session.refresh().await.unwrap();
+    session.validate().await.unwrap();
+
+    server.token_lifetime(Some(Duration::from_millis(0)));
+    assert!(session.validate().await.unwrap_err().is_token_invalid());
+}
+
+#[tokio::test]
+async fn test_faults() {
+    let server = TestServer::start().unwrap();
+    let session = login(&server, "steve@example.com").await;
+
+    server.status_override("/validate", Some(StatusCode::SERVICE_UNAVAILABLE));
+    let error = session.validate().await.unwrap_err();
+    assert_eq!(
+        error.response_info().unwrap().status,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    server.status_override("/validate", None);
+    session.validate().await.unwrap();
+
+    server.latency(Duration::from_millis(300));
+    let mut client = Client::with_http(
+        reqwest::Client::builder()
+            .timeout(Duration::from_millis(50))
+            .build()
+            .unwrap(),
+    );
+    client.retry_policy(RetryPolicy::none());
+    let pool = ServerPool::single(server.url().clone());
+    let error = Session::login_with_pool(&client, &pool, "steve@example.com", SEEDED_PASSWORD)
+        .await
+        .unwrap_err();
+    assert!(error.is_timeout());
+}
+
+#[tokio::test]
+async fn test_launch_arguments() {
+    let server = TestServer::start().unwrap();
+    server.add_account(
+        TestAccount::new("lapsed@example.com", SEEDED_PASSWORD)
+            .profile("Lapsed")
+            .owns_game(false),
+    );
+
+    let session = login(&server, "steve@example.com").await;
+    let substitutions = session.launch_arguments().unwrap().substitutions();
+    assert_eq!(substitutions["auth_player_name"], "Steve");
+    assert_eq!(substitutions["user_type"], "mojang");
+    let substitutions = session
+        .launch_arguments_checked()
+        .await
+        .unwrap()
+        .substitutions();
+    assert_eq!(substitutions["auth_player_name"], "Steve");
+
+    // Only the checked arguments ask for the entitlements
+    let session = login(&server, "lapsed@example.com").await;
+    let substitutions = session.launch_arguments().unwrap().substitutions();
+    assert_eq!(substitutions["auth_player_name"], "Lapsed");
+    let substitutions = session
+        .launch_arguments_checked()
+        .await
+        .unwrap()
+        .substitutions();
+    assert_eq!(substitutions["auth_player_name"], "Player");
+
+    let session = login(&server, "new@example.com").await;
+    assert!(matches!(
+        session.launch_arguments(),
+        Err(Error::MissingField("selected_profile"))
+    ));
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "server")]
+
+use sage_auth::password::{BlessingSkinCipher, BlessingSkinVerifier, PasswordVerifier};
+use sage_auth::throttle::{Throttle, ThrottleConfig};
+use sage_auth::{ApiError, Error};
+
+use std::net::IpAddr;
+use std::thread;
+use std::time::Duration;
+
+fn error_message(result: sage_auth::Result<()>) -> String {
+    match result {
+        Err(Error::API(ApiError::ForbiddenOperationException(message), None)) => message,
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_lockout() {
+    let throttle = Throttle::new(ThrottleConfig {
+        max_account_failures: 2,
+        base_lockout: Duration::from_millis(200),
+        ..ThrottleConfig::default()
+    });
+    // md5("password" . "salt")
+    let verifier = BlessingSkinVerifier::new(BlessingSkinCipher::Md5, "salt");
+    let hash = "b305cadbb3bce54f3aa59c64fec00dea";
+    let ip: IpAddr = "127.0.0.1".parse().unwrap();
+
+    assert!(throttle
+        .authenticate("Steve", ip, "password", Some(hash), &verifier)
+        .is_ok());
+
+    for _ in 0..2 {
+        assert_eq!(
+            error_message(throttle.authenticate("steve", ip, "wrong", Some(hash), &verifier)),
+            "Invalid credentials. Invalid username or password."
+        );
+    }
+
+    // Locked out, even with the right password.
+    assert_eq!(
+        error_message(throttle.authenticate("STEVE", ip, "password", Some(hash), &verifier)),
+        "Invalid credentials."
+    );
+    assert!(throttle.check("alex", ip).is_ok());
+
+    thread::sleep(Duration::from_millis(250));
+    assert!(throttle
+        .authenticate("steve", ip, "password", Some(hash), &verifier)
+        .is_ok());
+}
+
+#[test]
+fn test_unknown_account() {
+    let throttle = Throttle::default();
+    let verifier = BlessingSkinVerifier::new(BlessingSkinCipher::Salted2Sha256, "");
+    let ip: IpAddr = "::1".parse().unwrap();
+
+    assert_eq!(
+        error_message(throttle.authenticate("nobody", ip, "password", None, &verifier)),
+        "Invalid credentials. Invalid username or password."
+    );
+}
+
+#[test]
+fn test_blessing_skin_rehash() {
+    let verifier = BlessingSkinVerifier::new(BlessingSkinCipher::Salted2Md5, "");
+    // md5(md5("password"))
+    let legacy = "696d29e0940a4957748fe3fc9efd22a3";
+    assert!(verifier.verify("password", legacy));
+    assert!(verifier.needs_rehash(legacy));
+
+    let upgraded = verifier.hash("password").unwrap();
+    assert!(verifier.verify("password", &upgraded));
+    assert!(!verifier.verify("wrong", &upgraded));
+    assert!(!verifier.needs_rehash(&upgraded));
+}
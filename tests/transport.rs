@@ -0,0 +1,133 @@
+use reqwest::{Method, StatusCode};
+use sage_auth::auth::AuthenticateBuilder;
+use sage_auth::client::{Client, RetryPolicy};
+use sage_auth::session::Session;
+use sage_auth::transport::{MockResponse, MockTransport};
+use sage_auth::validate::ValidateBuilder;
+use sage_auth::Error;
+use serde_json::{json, Value};
+use std::time::Duration;
+use uuid::Uuid;
+
+fn client(mock: &MockTransport) -> Client {
+    let mut policy = RetryPolicy::new();
+    policy.base_delay(Duration::from_millis(1));
+    let mut client = Client::with_transport(mock.clone());
+    client.retry_policy(policy);
+    client
+}
+
+#[tokio::test]
+async fn test_session() {
+    let profile_id = Uuid::new_v4();
+    let mut mock = MockTransport::new();
+    mock.on(
+        Method::POST,
+        "/authenticate",
+        MockResponse::json(
+            StatusCode::OK,
+            &json!({
+                "accessToken": "ACCESS_TOKEN",
+                "clientToken": Uuid::nil(),
+                "availableProfiles": [],
+                "selectedProfile": {"id": profile_id.to_simple().to_string(), "name": "Steve"},
+            }),
+        ),
+    )
+    .on(
+        Method::POST,
+        "/session/minecraft/join",
+        MockResponse::status(StatusCode::NO_CONTENT),
+    );
+
+    let session = Session::login_with_client(&client(&mock), "USERNAME", "PASSWORD")
+        .await
+        .unwrap();
+    assert_eq!(session.profile().unwrap().name, "Steve");
+    session.join_server("SERVER_HASH").await.unwrap();
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    let join: Value = serde_json::from_slice(&requests[1].body).unwrap();
+    assert_eq!(join["accessToken"], "ACCESS_TOKEN");
+    assert_eq!(join["selectedProfile"], profile_id.to_simple().to_string());
+    assert_eq!(join["serverId"], "SERVER_HASH");
+}
+
+#[tokio::test]
+async fn test_malformed_json() {
+    let mut mock = MockTransport::new();
+    mock.on(
+        Method::POST,
+        "/authenticate",
+        MockResponse::body(StatusCode::OK, "{\"accessToken\":"),
+    );
+
+    let result = AuthenticateBuilder::new()
+        .client(&client(&mock))
+        .username("USERNAME")
+        .password("PASSWORD")
+        .request()
+        .await;
+    assert!(matches!(result, Err(Error::Json(_))));
+}
+
+#[tokio::test]
+async fn test_retries() {
+    let mut mock = MockTransport::new();
+    mock.on(
+        Method::POST,
+        "/validate",
+        MockResponse::too_many_requests(Duration::from_secs(0)),
+    )
+    .on(Method::POST, "/validate", MockResponse::Timeout)
+    .on(
+        Method::POST,
+        "/validate",
+        MockResponse::status(StatusCode::NO_CONTENT),
+    );
+
+    ValidateBuilder::new()
+        .client(&client(&mock))
+        .access_token("ACCESS_TOKEN")
+        .client_token(Uuid::nil())
+        .request()
+        .await
+        .unwrap();
+    assert_eq!(mock.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_timeout() {
+    let mut mock = MockTransport::new();
+    mock.on(Method::POST, "/authenticate", MockResponse::Timeout);
+
+    // Authenticating again could issue a second token, so it is not retried
+    let result = AuthenticateBuilder::new()
+        .client(&client(&mock))
+        .username("USERNAME")
+        .password("PASSWORD")
+        .request()
+        .await;
+    match result {
+        Err(error) => assert!(error.is_timeout() && error.is_retryable()),
+        Ok(_) => panic!("expected a timeout"),
+    }
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_unmatched() {
+    let mock = MockTransport::new();
+
+    let result = ValidateBuilder::new()
+        .client(&client(&mock))
+        .access_token("ACCESS_TOKEN")
+        .client_token(Uuid::nil())
+        .request()
+        .await;
+    match result {
+        Err(Error::Http(info)) => assert_eq!(info.status, StatusCode::NOT_FOUND),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}